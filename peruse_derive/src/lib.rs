@@ -0,0 +1,234 @@
+//! Derive macros for peruse.
+//!
+//! * TokenParsers - generates a slice parser for every variant of a token enum
+//! * FromCaptures - builds a struct from the named groups of a regex match
+//!
+//! These are re-exported from `peruse` itself when its `derive` feature is enabled.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// Words that can't be used as method names.  Variants whose snake case name is
+/// one of these get a trailing underscore instead, so `Token::If` becomes
+/// `Token::if_()`.
+const KEYWORDS: &[&str] = &[
+  "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+  "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+  "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+  "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+  "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Derive a parser constructor for every variant of a token enum.
+///
+/// For each variant a function named after the variant in snake case is
+/// added to the enum.  It returns a slice parser that matches a single token
+/// of that variant and extracts its fields:
+///
+/// * unit variants produce `()`
+/// * variants with one field produce that field
+/// * variants with several fields (named or not) produce a tuple of the
+///   fields in declaration order
+///
+/// Only the extracted fields are cloned, so they must implement `Clone` (the
+/// enum itself doesn't need to) and be `'static`.
+///
+/// # Examples
+/// ```
+/// # extern crate peruse;
+/// # #[macro_use] extern crate peruse_derive;
+/// # use peruse::parsers::*;
+/// #[derive(Debug, PartialEq, TokenParsers)]
+/// enum Token {
+///   PlusSign,
+///   Term(i32),
+/// }
+///
+/// # fn main() {
+/// let sum = Token::term().then_l(Token::plus_sign()).then(Token::term());
+/// let input = [Token::Term(1), Token::PlusSign, Token::Term(2)];
+/// assert_eq!(sum.parse(&input), Ok(((1, 2), &input[3..])));
+/// # }
+/// ```
+#[proc_macro_derive(TokenParsers)]
+pub fn derive_token_parsers(input: TokenStream) -> TokenStream {
+  let input = syn::parse_macro_input!(input as DeriveInput);
+  match token_parsers(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into(),
+  }
+}
+
+fn token_parsers(input: &DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+  let data = match input.data {
+    Data::Enum(ref data) => data,
+    _ => return Err(syn::Error::new_spanned(&input.ident, "TokenParsers can only be derived for enums")),
+  };
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+  let methods = data.variants.iter().map(|variant| {
+    let variant_name = &variant.ident;
+    let method = method_name(variant_name);
+    let doc = format!("Create a parser that matches a single `{}` token", variant_name);
+
+    let (pattern, output, value) = match variant.fields {
+      Fields::Unit => (quote!(#name::#variant_name), quote!(()), quote!(())),
+      Fields::Unnamed(ref fields) => {
+        let bindings: Vec<Ident> = (0..fields.unnamed.len()).map(|i| Ident::new(&format!("f{}", i), Span::call_site())).collect();
+        let types: Vec<_> = fields.unnamed.iter().map(|f| &f.ty).collect();
+        let (output, value) = extracted(&bindings, &types);
+        (quote!(#name::#variant_name(#(ref #bindings),*)), output, value)
+      },
+      Fields::Named(ref fields) => {
+        let bindings: Vec<Ident> = fields.named.iter().map(|f| f.ident.clone().unwrap()).collect();
+        let types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+        let (output, value) = extracted(&bindings, &types);
+        (quote!(#name::#variant_name{#(ref #bindings),*}), output, value)
+      },
+    };
+
+    quote! {
+      #[doc = #doc]
+      #[allow(unreachable_patterns)]
+      pub fn #method() -> ::peruse::slice_parsers::RefMatchParser<Self, #output, fn(&Self) -> Option<#output>> {
+        let extract: fn(&Self) -> Option<#output> = |token| match *token {
+          #pattern => Some(#value),
+          _ => None,
+        };
+        ::peruse::slice_parsers::ref_matcher(extract)
+      }
+    }
+  });
+
+  Ok(quote! {
+    impl #impl_generics #name #ty_generics #where_clause {
+      #(#methods)*
+    }
+  })
+}
+
+/// Derive `peruse::string_parsers::FromCaptures` for a struct with named fields.
+///
+/// Each field is parsed with `FromStr` from the regex capture group with the
+/// same name.  Fields of type `Option<T>` are `None` when their group didn't
+/// participate in the match, every other field requires its group.
+///
+/// # Examples
+/// ```
+/// # extern crate peruse;
+/// # #[macro_use] extern crate peruse_derive;
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// #[derive(Debug, PartialEq, FromCaptures)]
+/// struct Version {
+///   major: u32,
+///   minor: u32,
+///   tag: Option<String>,
+/// }
+///
+/// # fn main() {
+/// let parser = named_capture::<Version>(r"(?P<major>\d+)\.(?P<minor>\d+)(-(?P<tag>\w+))?");
+/// assert_eq!(parser.parse("1.2 "), Ok((Version{major: 1, minor: 2, tag: None}, " ")));
+/// # }
+/// ```
+#[proc_macro_derive(FromCaptures)]
+pub fn derive_from_captures(input: TokenStream) -> TokenStream {
+  let input = syn::parse_macro_input!(input as DeriveInput);
+  match from_captures(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into(),
+  }
+}
+
+fn from_captures(input: &DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+  let fields = match input.data {
+    Data::Struct(ref data) => match data.fields {
+      Fields::Named(ref fields) => fields,
+      _ => return Err(syn::Error::new_spanned(&input.ident, "FromCaptures can only be derived for structs with named fields")),
+    },
+    _ => return Err(syn::Error::new_spanned(&input.ident, "FromCaptures can only be derived for structs")),
+  };
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+  let values = fields.named.iter().map(|field| {
+    let ident = field.ident.as_ref().unwrap();
+    let group = ident.to_string();
+    if is_option(&field.ty) {
+      quote!(#ident: captures.parse_opt(#group)?)
+    } else {
+      quote!(#ident: captures.parse(#group)?)
+    }
+  });
+
+  Ok(quote! {
+    impl #impl_generics ::peruse::string_parsers::FromCaptures for #name #ty_generics #where_clause {
+      fn from_captures(captures: &::peruse::string_parsers::NamedCaptures) -> Result<Self, String> {
+        Ok(#name{ #(#values),* })
+      }
+    }
+  })
+}
+
+/// Whether a field's type is written as `Option<T>`
+fn is_option(ty: &Type) -> bool {
+  let segment = match *ty {
+    Type::Path(ref path) => match path.path.segments.last() {
+      Some(segment) => segment,
+      None => return false,
+    },
+    _ => return false,
+  };
+  match segment.arguments {
+    PathArguments::AngleBracketed(ref args) => {
+      segment.ident == "Option" && args.args.len() == 1 && matches!(args.args[0], GenericArgument::Type(_))
+    },
+    _ => false,
+  }
+}
+
+/// The output type and value expression for a variant with the given fields
+fn extracted(bindings: &[Ident], types: &[&syn::Type]) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+  if bindings.len() == 1 {
+    let binding = &bindings[0];
+    let ty = types[0];
+    (quote!(#ty), quote!(#binding.clone()))
+  } else {
+    (quote!((#(#types),*)), quote!((#(#bindings.clone()),*)))
+  }
+}
+
+fn method_name(variant: &Ident) -> Ident {
+  let name = snake_case(&variant.to_string());
+  if KEYWORDS.contains(&name.as_str()) {
+    Ident::new(&format!("{}_", name), variant.span())
+  } else {
+    Ident::new(&name, variant.span())
+  }
+}
+
+/// Convert a CamelCase variant name to snake_case, keeping acronyms together
+/// (`HTTPHeader` becomes `http_header`)
+fn snake_case(name: &str) -> String {
+  let chars: Vec<char> = name.chars().collect();
+  let mut out = String::new();
+  for (i, c) in chars.iter().enumerate() {
+    if c.is_uppercase() && i > 0 {
+      let prev = chars[i - 1];
+      let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+      if prev != '_' && (prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower)) {
+        out.push('_');
+      }
+    }
+    out.extend(c.to_lowercase());
+  }
+  out
+}
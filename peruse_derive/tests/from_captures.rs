@@ -0,0 +1,36 @@
+extern crate peruse;
+#[macro_use]
+extern crate peruse_derive;
+
+use peruse::parsers::*;
+use peruse::string_parsers::*;
+
+#[derive(Debug, PartialEq, FromCaptures)]
+struct Entry {
+  level: String,
+  code: u8,
+  detail: Option<i32>,
+}
+
+#[test]
+fn test_struct_fields() {
+  let parser = named_capture::<Entry>(r"(?P<level>[A-Z]+) (?P<code>\d+)(:(?P<detail>-?\d+))?");
+  let expected = Entry{level: "WARN".to_string(), code: 12, detail: Some(-3)};
+  assert_eq!(parser.parse("WARN 12:-3\n"), Ok((expected, "\n")));
+
+  let expected2 = Entry{level: "INFO".to_string(), code: 7, detail: None};
+  assert_eq!(parser.parse("INFO 7"), Ok((expected2, "")));
+}
+
+#[test]
+fn test_conversion_error() {
+  let parser = named_capture::<Entry>(r"(?P<level>[A-Z]+) (?P<code>\d+)");
+  let err = parser.parse("WARN 300").unwrap_err();
+  assert!(err.starts_with("Invalid value for capture group `code` at offset 5"), "{}", err);
+}
+
+#[test]
+fn test_missing_group() {
+  let parser = named_capture::<Entry>(r"(?P<level>[A-Z]+)");
+  assert_eq!(parser.parse("WARN"), Err("Missing capture group `code`".to_string()));
+}
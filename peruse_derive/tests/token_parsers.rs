@@ -0,0 +1,45 @@
+extern crate peruse;
+#[macro_use]
+extern crate peruse_derive;
+
+use peruse::parsers::*;
+
+#[derive(Debug, PartialEq, TokenParsers)]
+enum Token {
+  PlusSign,
+  If,
+  HTTPHeader(String),
+  Term(i32),
+  Range(i32, i32),
+  Call{name: String, args: usize},
+}
+
+#[test]
+fn test_unit_variant() {
+  let input = [Token::PlusSign, Token::If];
+  assert_eq!(Token::plus_sign().parse(&input), Ok(((), &input[1..])));
+  assert_eq!(Token::if_().parse(&input[1..]), Ok(((), &input[2..])));
+  assert!(Token::plus_sign().parse(&input[1..]).is_err());
+}
+
+#[test]
+fn test_field_variants() {
+  let input = [
+    Token::Term(4),
+    Token::Range(1, 5),
+    Token::Call{name: "f".to_string(), args: 2},
+    Token::HTTPHeader("Host".to_string()),
+  ];
+  let parser = Token::term()
+    .then(Token::range())
+    .then(Token::call())
+    .then(Token::http_header());
+  assert_eq!(parser.parse(&input), Ok(((((4, (1, 5)), ("f".to_string(), 2)), "Host".to_string()), &input[4..])));
+}
+
+#[test]
+fn test_expression() {
+  let sum = repsep(Token::term(), Token::plus_sign()).map(|v| v.iter().sum::<i32>());
+  let input = [Token::Term(1), Token::PlusSign, Token::Term(2), Token::PlusSign, Token::Term(3)];
+  assert_eq!(sum.parse(&input), Ok((6, &input[5..])));
+}
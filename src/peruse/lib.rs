@@ -5,18 +5,31 @@
 //! * slice_parsers - a few simple primitive parsers for handling slices of items
 //! * string_parsers - a few parsers for handling strings
 //!
+//! With the `derive` feature enabled, `#[derive(TokenParsers)]` generates a slice parser for every
+//! variant of a token enum, and `#[derive(FromCaptures)]` lets a struct be built from the named
+//! groups of a regex match (see the `peruse_derive` crate).
+//!
 //! Parsers work by essentially building a heirarchy of structs that all implement the `Parser` and
 //! `Combinator` traits.  
 
 
 
 extern crate regex;
+extern crate regex_syntax;
+extern crate unicode_xid;
+#[cfg(feature = "derive")]
+extern crate peruse_derive;
+
+#[cfg(feature = "derive")]
+pub use peruse_derive::{FromCaptures, TokenParsers};
 
 pub mod parsers;
 pub mod slice_parsers;
 pub mod string_parsers;
 
+#[cfg(test)]
 mod slice_parser_tests;
+#[cfg(test)]
 mod string_parser_tests;
 //mod tests;
 
@@ -1,4 +1,5 @@
 use std::rc::Rc;
+use std::marker::PhantomData;
 
 
 /////////     TRAITS/TYPES       //////////
@@ -36,12 +37,41 @@ pub trait ParserCombinator : Parser + Clone {
   fn repeat(&self) -> RepeatParser<Self> {
     RepeatParser{parser: self.clone()}
   }
+
+  /// Like `repeat`, but instead of building a vector, each parsed value is
+  /// folded into an accumulator that starts as a copy of `init`
+  fn fold<A: Clone, F: 'static + Fn(A, Self::O) -> A>(&self, init: A, f: F) -> FoldParser<Self, A> {
+    FoldParser{parser: self.clone(), init, folder: Rc::new(f)}
+  }
+
+  /// Like `repeat`, but the parsed values are collected into any default-constructible container
+  /// that can be extended with them, such as a `HashMap` or `String`
+  fn collect<C: Default + Extend<Self::O>>(&self) -> CollectParser<Self, C> {
+    CollectParser{parser: self.clone(), _marker: PhantomData}
+  }
   
+  /// Lazily apply this parser to the input over and over, yielding one parsed value at a time.
+  /// See `ParseIter` for details
+  fn parse_iter<'a>(&self, data: &'a Self::I) -> ParseIter<'a, Self> {
+    ParseIter{parser: self.clone(), remain: data, done: false}
+  }
+
   /// Map the value of this parser
   fn map<T, F: 'static + Fn(Self::O) -> T>(&self, f: F) -> MapParser<Self::I, Self, T> {
     MapParser{parser: self.clone(), mapper: Rc::new(Box::new(f))}
   }
 
+  /// Replace the value of this parser with a copy of the given value
+  fn value<T: 'static + Clone>(&self, v: T) -> MapParser<Self::I, Self, T> {
+    self.map(move |_| v.clone())
+  }
+
+  /// Only accept the value of this parser if it satisfies the given predicate, otherwise fail with
+  /// the given error message
+  fn verify<F: Fn(&Self::O) -> bool>(&self, f: F, message: &str) -> VerifyParser<Self, F> {
+    VerifyParser{parser: self.clone(), predicate: Rc::new(f), message: message.to_string()}
+  }
+
   /// Create a disjunction with another parser.  If this parser produces an error, the other parser will be used
   fn or<P: Parser<I=Self::I, O=Self::O>>(&self, p: P) -> OrParser<Self,P> {
     OrParser{first: self.clone(), second: p}
@@ -50,6 +80,26 @@ pub trait ParserCombinator : Parser + Clone {
 
 }
 
+/// Input types that can report how much data remains.  This is used by
+/// parsers that need to compare how far different alternatives got, such as
+/// `LongestParser`.
+pub trait Input {
+  /// The number of items (elements for slices, bytes for strings) left in the input
+  fn input_len(&self) -> usize;
+}
+
+impl<T> Input for [T] {
+  fn input_len(&self) -> usize {
+    self.len()
+  }
+}
+
+impl Input for str {
+  fn input_len(&self) -> usize {
+    self.len()
+  }
+}
+
 /// The result of a parser's attempt to parse input data.  
 ///
 /// A successful result contains the output value of the parser along with a new input value that
@@ -82,6 +132,54 @@ pub fn opt<T: Parser>(t: T) -> OptionParser<T> {
   OptionParser{parser: t}
 }
 
+/// Create a parser that consumes nothing and always returns a copy of the given value
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [1, 2];
+/// let parser = lit(2).or(succeed(0));
+/// assert_eq!(parser.parse(&input), Ok((0, &input[0..])));
+/// ```
+///
+pub fn succeed<I: ?Sized, T: Clone>(v: T) -> SucceedParser<I, T> {
+  SucceedParser{value: v, _marker: PhantomData}
+}
+
+/// Create a parser that consumes nothing and always fails with the given message
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [1, 2];
+/// let parser = lit(2).or(fail("expected a 2"));
+/// assert_eq!(parser.parse(&input), Err("expected a 2".to_string()));
+/// ```
+///
+pub fn fail<I: ?Sized, T>(message: &str) -> FailParser<I, T> {
+  FailParser{message: message.to_string(), _marker: PhantomData}
+}
+
+/// Create a parser that only uses the given parser if `enabled` is true.  When
+/// enabled the result is wrapped in `Some` and errors are passed through,
+/// otherwise `None` is returned without consuming anything.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [1, 2];
+/// assert_eq!(cond(true, lit(1)).parse(&input), Ok((Some(1), &input[1..])));
+/// assert_eq!(cond(false, lit(1)).parse(&input), Ok((None, &input[0..])));
+/// assert!(cond(true, lit(2)).parse(&input).is_err());
+/// ```
+///
+pub fn cond<P: Parser>(enabled: bool, p: P) -> CondParser<P> {
+  CondParser{enabled, parser: p}
+}
+
 /// Create a lazily evaluated parser from a function.  This can be used to generate recursive parsers
 ///
 /// # Examples
@@ -90,7 +188,7 @@ pub fn opt<T: Parser>(t: T) -> OptionParser<T> {
 /// # use peruse::parsers::*;
 /// # use peruse::slice_parsers::lit;
 /// 
-/// fn recurse() -> Box<Parser<I=[i32], O=i32>> {
+/// fn recurse() -> Box<dyn Parser<I=[i32], O=i32>> {
 ///   let end = lit(1).map(|_| 0);
 ///   let rec = lit(0).then_r(recursive(|| recurse())).map(|t| t + 1);
 ///   Box::new(end.or(rec))
@@ -99,7 +197,7 @@ pub fn opt<T: Parser>(t: T) -> OptionParser<T> {
 /// # assert_eq!(recurse().parse(&input), Ok((3, &input[4..])));
 /// ```
 ///
-pub fn recursive<I:?Sized,O, F:  Fn() -> Box<Parser<I=I,O=O>>>(f: F) -> RecursiveParser<I,O,F> {
+pub fn recursive<I:?Sized,O, F:  Fn() -> Box<dyn Parser<I=I,O=O>>>(f: F) -> RecursiveParser<I,O,F> {
   RecursiveParser{parser: Rc::new(f)}
 }
 
@@ -122,7 +220,60 @@ pub fn recursive<I:?Sized,O, F:  Fn() -> Box<Parser<I=I,O=O>>>(f: F) -> Recursiv
 /// // Err
 /// ```
 pub fn repsep<I: ?Sized, A: Parser<I=I>, B: Parser<I=I>>(rep: A, sep: B) -> RepSepParser<A,B> {
-  RepSepParser{rep: rep, sep: sep, min_reps: 1}
+  RepSepParser{rep, sep, min_reps: 1}
+}
+
+/// Create a parser that works like `repsep`, but folds the `rep` results into
+/// an accumulator instead of building a vector.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [2, 0, 2, 0, 2, 4];
+/// let parser = repsep_fold(lit(2), lit(0), 0, |sum, i| sum + i);
+/// assert_eq!(parser.parse(&input), Ok((6, &input[5..])));
+/// ```
+pub fn repsep_fold<I: ?Sized, A: Parser<I=I>, B: Parser<I=I>, Acc: Clone, F: 'static + Fn(Acc, A::O) -> Acc>(rep: A, sep: B, init: Acc, f: F) -> RepSepFoldParser<A, B, Acc> {
+  RepSepFoldParser{rep, sep, min_reps: 1, init, folder: Rc::new(f)}
+}
+
+/// Create a parser that works like `repsep`, but collects the `rep` results
+/// into any default-constructible container that can be extended with them.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// # use std::collections::HashSet;
+/// let input = [2, 0, 3, 0, 2, 4];
+/// let parser = repsep_collect::<_, _, _, HashSet<i32>>(lit(2).or(lit(3)), lit(0));
+/// let (set, _) = parser.parse(&input).unwrap();
+/// assert_eq!(set.len(), 2);
+/// ```
+pub fn repsep_collect<I: ?Sized, A: Parser<I=I>, B: Parser<I=I>, C: Default + Extend<A::O>>(rep: A, sep: B) -> RepSepCollectParser<A, B, C> {
+  RepSepCollectParser{rep, sep, min_reps: 1, _marker: PhantomData}
+}
+
+/// Create a parser that repeatedly uses the `item` parser until the `end`
+/// parser succeeds.  Before each item the `end` parser is tried first, and
+/// once it matches the collected items are returned along with the value from
+/// `end`.  The parser fails if `item` fails or succeeds without consuming any
+/// input, or if the input runs out before `end` matches.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [1, 2, 1, 0, 5];
+/// let parser = many_till(lit(1).or(lit(2)), lit(0));
+/// assert_eq!(parser.parse(&input), Ok(((vec![1, 2, 1], 0), &input[4..])));
+///
+/// //no terminator
+/// assert!(parser.parse(&[1, 2, 1]).is_err());
+/// ```
+pub fn many_till<I: ?Sized + Input, A: Parser<I=I>, B: Parser<I=I>>(item: A, end: B) -> ManyTillParser<A, B> {
+  ManyTillParser{item, end}
 }
 
 /// Create a parser that attempts to use each of the given parsers until one succeeds.  If all the
@@ -160,6 +311,30 @@ pub fn one_of<T: Parser>(t: Vec<T>) -> OneOfParser<T> {
   OneOfParser{options: t}
 }
 
+/// Create a parser that tries every one of the given parsers and returns the
+/// result of whichever consumed the most input.  If several parsers consume
+/// the same amount, the one that comes first in the vector wins.  Like
+/// `one_of`, parsers of different types must be boxed.
+///
+/// # Examples
+///
+/// With `one_of` the first option would match and leave the `2` unparsed,
+/// but `longest` picks the option that consumes both elements.
+///
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [1, 2, 3];
+/// let p1 = boxed(lit(1).map(|_| "one"));
+/// let p2 = boxed(lit(1).then(lit(2)).map(|_| "one two"));
+/// let parser = longest(vec![p1, p2]);
+/// assert_eq!(parser.parse(&input), Ok(("one two", &input[2..])));
+/// ```
+///
+pub fn longest<T: Parser>(t: Vec<T>) -> LongestParser<T> {
+  LongestParser{options: t}
+}
+
 /// Wrap a boxed parser.  This mostly exists to avoid slow compile times.  Boxing a complex parser into a
 /// trait object keep compile times down as the boxed parser is combined with other parsers
 ///
@@ -175,6 +350,46 @@ pub fn boxed<I: ?Sized,O, P:'static + Parser<I=I, O=O> >(p: P) -> BoxedParser<I,
   BoxedParser{parser: Rc::new(Box::new(p))}
 }
 
+/// Create a parser that accepts each of the given parsers at most once, in
+/// any order.  The members are given as a tuple (up to 8 members) and the
+/// outputs are returned as a tuple in the same order the members were
+/// declared, regardless of the order they appeared in the input.  Members are
+/// required unless they are wrapped with `optional`, in which case their
+/// output is an `Option`.
+///
+/// The parser stops once every member has matched or none of the remaining
+/// members match.  Members that succeed without consuming any input (such as
+/// `opt` parsers) are only used once nothing else matches.  It fails if a
+/// required member never matched, or if the input following the permutation
+/// starts with a member that already matched.  This means consecutive
+/// permutations must be split by a separator or terminator, for example with
+/// `repsep`.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let parser = permutation((lit(1), optional(lit(2)), lit(3)));
+///
+/// let input = [3, 1, 4];
+/// assert_eq!(parser.parse(&input), Ok(((1, None, 3), &input[2..])));
+///
+/// let input2 = [2, 3, 1];
+/// assert_eq!(parser.parse(&input2), Ok(((1, Some(2), 3), &input2[3..])));
+///
+/// assert!(parser.parse(&[1, 3, 1]).is_err()); //duplicate
+/// assert!(parser.parse(&[1, 2]).is_err()); //missing 3
+/// ```
+pub fn permutation<P: PermutationMembers>(members: P) -> PermutationParser<P> {
+  PermutationParser{members}
+}
+
+/// Mark a member of a `permutation` as optional.  Unlike `opt`, this is not a
+/// parser on its own and can only be used as a member of a permutation.
+pub fn optional<P: Parser>(p: P) -> OptionalMember<P> {
+  OptionalMember{parser: p}
+}
+
 
 ////////////    STRUCTS     //////////////
 
@@ -220,19 +435,10 @@ impl<T: Parser> Parser for RepeatParser<T> {
   type O = Vec<T::O>;
   
   fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
-    let mut remain = data;
-    let mut v: Vec<T::O> = Vec::new();
-    loop {
-      match self.parser.parse(remain.clone()) {
-        Ok((result, rest)) => {
-          v.push(result);
-          remain = rest;
-        }
-        Err(_) => {
-          return Ok((v, remain));
-        }
-      }
-    }
+    Ok(fold_repeat(&self.parser, data, Vec::new(), |mut v, result| {
+      v.push(result);
+      v
+    }))
   }
 }
 
@@ -244,11 +450,144 @@ impl<T: ParserCombinator> Clone for RepeatParser<T> {
   }
 }
 
+/// Repeatedly apply `parser` until it returns an error, folding each result into the accumulator
+fn fold_repeat<'a, P: Parser, A, F: Fn(A, P::O) -> A>(parser: &P, data: &'a P::I, init: A, f: F) -> (A, &'a P::I) {
+  let mut remain = data;
+  let mut acc = init;
+  loop {
+    match parser.parse(remain) {
+      Ok((result, rest)) => {
+        acc = f(acc, result);
+        remain = rest;
+      }
+      Err(_) => {
+        return (acc, remain);
+      }
+    }
+  }
+}
+
+
+/// A Parser that repeats the given parser until it encounters an error,
+/// folding each parsed value into an accumulator
+pub struct FoldParser<P: Parser, A> {
+  parser: P,
+  init: A,
+  folder: Rc<dyn Fn(A, P::O) -> A>,
+}
+
+impl<P: Parser, A: Clone> Parser for FoldParser<P, A> {
+  type I = P::I;
+  type O = A;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    Ok(fold_repeat(&self.parser, data, self.init.clone(), |acc, result| (self.folder)(acc, result)))
+  }
+}
+
+impl<P: ParserCombinator, A: Clone> ParserCombinator for FoldParser<P, A> {}
+
+impl<P: ParserCombinator, A: Clone> Clone for FoldParser<P, A> {
+  fn clone(&self) -> Self {
+    FoldParser{parser: self.parser.clone(), init: self.init.clone(), folder: self.folder.clone()}
+  }
+}
+
+
+/// A Parser that repeats the given parser until it encounters an error,
+/// collecting the parsed values into a container
+pub struct CollectParser<P: Parser, C> {
+  parser: P,
+  _marker: PhantomData<C>
+}
+
+impl<P: Parser, C: Default + Extend<P::O>> Parser for CollectParser<P, C> {
+  type I = P::I;
+  type O = C;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    Ok(fold_repeat(&self.parser, data, C::default(), |mut c, result| {
+      c.extend(Some(result));
+      c
+    }))
+  }
+}
+
+impl<P: ParserCombinator, C: Default + Extend<P::O>> ParserCombinator for CollectParser<P, C> {}
+
+impl<P: ParserCombinator, C> Clone for CollectParser<P, C> {
+  fn clone(&self) -> Self {
+    CollectParser{parser: self.parser.clone(), _marker: PhantomData}
+  }
+}
+
+
+/// An iterator that repeatedly applies a parser to its input, yielding each
+/// parsed value as soon as it's available.  This is the lazy equivalent of
+/// `RepeatParser`, useful for large inputs that shouldn't be collected into a
+/// single vector.
+///
+/// Iteration ends once the input is exhausted.  If the parser fails (or
+/// succeeds without consuming anything) while input remains, the error is
+/// yielded and iteration stops.  Either way, `remaining` returns the input
+/// that has not been consumed.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::lit;
+/// let input = [1, 0, 1, 0, 2];
+/// let mut iter = lit(1).then(lit(0)).parse_iter(&input);
+/// assert_eq!(iter.next(), Some(Ok((1, 0))));
+/// assert_eq!(iter.next(), Some(Ok((1, 0))));
+/// assert!(iter.next().unwrap().is_err());
+/// assert_eq!(iter.next(), None);
+/// assert_eq!(iter.remaining(), &input[4..]);
+/// ```
+pub struct ParseIter<'a, P: Parser> where P::I: 'a {
+  parser: P,
+  remain: &'a P::I,
+  done: bool,
+}
+
+impl<'a, P: Parser> ParseIter<'a, P> {
+
+  /// The portion of the input that has not yet been consumed
+  pub fn remaining(&self) -> &'a P::I {
+    self.remain
+  }
+}
+
+impl<'a, P: Parser> Iterator for ParseIter<'a, P> where P::I: Input {
+  type Item = Result<P::O, String>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.done || self.remain.input_len() == 0 {
+      return None;
+    }
+    match self.parser.parse(self.remain) {
+      Ok((result, rest)) => {
+        if rest.input_len() == self.remain.input_len() {
+          self.done = true;
+          Some(Err("Parser did not consume any input".to_string()))
+        } else {
+          self.remain = rest;
+          Some(Ok(result))
+        }
+      }
+      Err(err) => {
+        self.done = true;
+        Some(Err(err))
+      }
+    }
+  }
+}
+
 
 /// A Parser that uses a closure to map the result of another parser
 pub struct MapParser<I: ?Sized, P: Parser<I=I>, T> {
   parser: P,
-  mapper: Rc<Box<Fn(P::O) -> T>>,
+  mapper: Rc<Box<dyn Fn(P::O) -> T>>,
 }
 
 impl<I: ?Sized, P: Parser<I=I>, T> Parser for MapParser<I,P,T> {
@@ -270,6 +609,38 @@ impl<I: ?Sized, P: ParserCombinator<I=I>, T> Clone for MapParser<I,P,T> {
 
 impl<I: ?Sized, P: ParserCombinator<I=I>, T> ParserCombinator for MapParser<I,P,T> {}
 
+/// A Parser that fails if the result of another parser does not satisfy a predicate
+pub struct VerifyParser<P: Parser, F: Fn(&P::O) -> bool> {
+  parser: P,
+  predicate: Rc<F>,
+  message: String,
+}
+
+impl<P: Parser, F: Fn(&P::O) -> bool> Parser for VerifyParser<P, F> {
+  type I = P::I;
+  type O = P::O;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    match self.parser.parse(data) {
+      Ok((result, rest)) => if (self.predicate)(&result) {
+        Ok((result, rest))
+      } else {
+        Err(self.message.clone())
+      },
+      Err(err) => Err(err)
+    }
+  }
+}
+
+impl<P: ParserCombinator, F: Fn(&P::O) -> bool> Clone for VerifyParser<P, F> {
+
+  fn clone(&self) -> Self {
+    VerifyParser{parser: self.parser.clone(), predicate: self.predicate.clone(), message: self.message.clone()}
+  }
+}
+
+impl<P: ParserCombinator, F: Fn(&P::O) -> bool> ParserCombinator for VerifyParser<P, F> {}
+
 pub struct OrParser<S: Parser,T: Parser> {
   first: S,
   second: T,
@@ -280,9 +651,9 @@ impl<I:?Sized,O, S: Parser<I=I,O=O>, T: Parser<I=I,O=O>> Parser for OrParser<S,T
   type O = O;
 
   fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
-    match self.first.parse(data.clone()) {
+    match self.first.parse(data) {
       Ok((a, d2)) => Ok((a, d2)),
-      Err(_) => match self.second.parse(data.clone()) {
+      Err(_) => match self.second.parse(data) {
         Ok((b, remain)) => Ok((b, remain)),
         Err(err) => Err(err)
       }
@@ -309,7 +680,7 @@ impl<P: Parser> Parser for OptionParser<P> {
   type O = Option<P::O>;
 
   fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
-    match self.parser.parse(data.clone()) {
+    match self.parser.parse(data) {
       Ok((result, rest))  => Ok((Some(result), rest)),
       Err(_)              => Ok((None, data)),
     }
@@ -318,11 +689,82 @@ impl<P: Parser> Parser for OptionParser<P> {
 
 impl<P: ParserCombinator> ParserCombinator for OptionParser<P> {}
 
-pub struct RecursiveParser<I: ?Sized, O, F> where F: Fn() -> Box<Parser<I=I,O=O>>{
+/// A Parser that consumes nothing and always returns the same value
+pub struct SucceedParser<I: ?Sized, T> {
+  value: T,
+  _marker: PhantomData<I>
+}
+
+impl<I: ?Sized, T: Clone> Parser for SucceedParser<I, T> {
+  type I = I;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    Ok((self.value.clone(), data))
+  }
+}
+
+impl<I: ?Sized, T: Clone> Clone for SucceedParser<I, T> {
+  fn clone(&self) -> Self {
+    SucceedParser{value: self.value.clone(), _marker: PhantomData}
+  }
+}
+
+impl<I: ?Sized, T: Clone> ParserCombinator for SucceedParser<I, T> {}
+
+
+/// A Parser that consumes nothing and always fails
+pub struct FailParser<I: ?Sized, T> {
+  message: String,
+  _marker: PhantomData<fn(&I) -> T>
+}
+
+impl<I: ?Sized, T> Parser for FailParser<I, T> {
+  type I = I;
+  type O = T;
+
+  fn parse<'a>(&self, _: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    Err(self.message.clone())
+  }
+}
+
+impl<I: ?Sized, T> Clone for FailParser<I, T> {
+  fn clone(&self) -> Self {
+    FailParser{message: self.message.clone(), _marker: PhantomData}
+  }
+}
+
+impl<I: ?Sized, T> ParserCombinator for FailParser<I, T> {}
+
+
+/// A Parser that only uses its inner parser when enabled
+#[derive(Clone)]
+pub struct CondParser<P: Parser> {
+  enabled: bool,
+  parser: P
+}
+
+impl<P: Parser> Parser for CondParser<P> {
+  type I = P::I;
+  type O = Option<P::O>;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    if self.enabled {
+      self.parser.parse(data).map(|(result, rest)| (Some(result), rest))
+    } else {
+      Ok((None, data))
+    }
+  }
+}
+
+impl<P: ParserCombinator> ParserCombinator for CondParser<P> {}
+
+
+pub struct RecursiveParser<I: ?Sized, O, F> where F: Fn() -> Box<dyn Parser<I=I,O=O>>{
   parser: Rc<F>
 }
 
-impl<I:?Sized, O, F> Parser for RecursiveParser<I, O, F> where F: Fn() -> Box<Parser<I=I,O=O>> {
+impl<I:?Sized, O, F> Parser for RecursiveParser<I, O, F> where F: Fn() -> Box<dyn Parser<I=I,O=O>> {
 
   type I = I;
   type O = O;
@@ -333,9 +775,9 @@ impl<I:?Sized, O, F> Parser for RecursiveParser<I, O, F> where F: Fn() -> Box<Pa
 
 }
 
-impl<I:?Sized, O, F> ParserCombinator for RecursiveParser<I, O, F> where F: Fn() -> Box<Parser<I=I,O=O>> {}
+impl<I:?Sized, O, F> ParserCombinator for RecursiveParser<I, O, F> where F: Fn() -> Box<dyn Parser<I=I,O=O>> {}
 
-impl<I: ?Sized, O, F> Clone for RecursiveParser<I, O, F> where F: Fn() -> Box<Parser<I=I,O=O>> {
+impl<I: ?Sized, O, F> Clone for RecursiveParser<I, O, F> where F: Fn() -> Box<dyn Parser<I=I,O=O>> {
   fn clone(&self) -> Self {
     RecursiveParser{parser: self.parser.clone()}
   }
@@ -355,41 +797,110 @@ impl<I: ?Sized, A: Parser<I=I>, B: Parser<I=I>> Parser for RepSepParser<A,B> {
   type O = Vec<A::O>;
 
   fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
-    let mut remain = data;
-    let mut v: Vec<A::O> = Vec::new();
-    loop {
-      match self.rep.parse(remain) {
-        Ok((result, rest)) => {
-          v.push(result);
-          match self.sep.parse(rest.clone()) {
-            Ok((_, rest2)) => {
-              remain = rest2
-            }
-            Err(_) => {
-              if v.len() < self.min_reps {
-                return Err(format!("Not enough reps: required {}, got {}", self.min_reps, v.len()))
-              } else {
-                return Ok((v, rest))
-              }
+    fold_repsep(&self.rep, &self.sep, self.min_reps, data, Vec::new(), |mut v, result| {
+      v.push(result);
+      v
+    })
+  }
+}
+
+impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>> ParserCombinator for RepSepParser<A,B> {}
+
+impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>> Clone for RepSepParser<A,B> {
+  
+  fn clone(&self) -> Self {
+    RepSepParser{rep : self.rep.clone(), sep: self.sep.clone(), min_reps: self.min_reps}
+  }
+
+}
+
+/// Repeatedly apply `rep` and `sep` in sequence until `sep` returns an error,
+/// folding each `rep` result into the accumulator
+fn fold_repsep<'a, I: ?Sized, A: Parser<I=I>, B: Parser<I=I>, Acc, F: Fn(Acc, A::O) -> Acc>(rep: &A, sep: &B, min_reps: usize, data: &'a I, init: Acc, f: F) -> ParseResult<&'a I, Acc> {
+  let mut remain = data;
+  let mut acc = init;
+  let mut reps = 0;
+  loop {
+    match rep.parse(remain) {
+      Ok((result, rest)) => {
+        acc = f(acc, result);
+        reps += 1;
+        match sep.parse(rest) {
+          Ok((_, rest2)) => {
+            remain = rest2
+          }
+          Err(_) => {
+            if reps < min_reps {
+              return Err(format!("Not enough reps: required {}, got {}", min_reps, reps))
+            } else {
+              return Ok((acc, rest))
             }
           }
         }
-        Err(err) => {
-          return Err(format!("Error on rep: {}", err));
-        }
+      }
+      Err(err) => {
+        return Err(format!("Error on rep: {}", err));
       }
     }
   }
 }
 
-impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>> ParserCombinator for RepSepParser<A,B> {}
 
-impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>> Clone for RepSepParser<A,B> {
-  
+/// A Parser that works like `RepSepParser`, but folds the `rep` results into an
+/// accumulator instead of building a vector
+pub struct RepSepFoldParser<A: Parser, B, Acc> {
+  pub rep: A,
+  pub sep: B,
+  pub min_reps: usize,
+  init: Acc,
+  folder: Rc<dyn Fn(Acc, A::O) -> Acc>,
+}
+
+impl<I: ?Sized, A: Parser<I=I>, B: Parser<I=I>, Acc: Clone> Parser for RepSepFoldParser<A, B, Acc> {
+  type I = I;
+  type O = Acc;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    fold_repsep(&self.rep, &self.sep, self.min_reps, data, self.init.clone(), |acc, result| (self.folder)(acc, result))
+  }
+}
+
+impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>, Acc: Clone> ParserCombinator for RepSepFoldParser<A, B, Acc> {}
+
+impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>, Acc: Clone> Clone for RepSepFoldParser<A, B, Acc> {
   fn clone(&self) -> Self {
-    RepSepParser{rep : self.rep.clone(), sep: self.sep.clone(), min_reps: self.min_reps}
+    RepSepFoldParser{rep: self.rep.clone(), sep: self.sep.clone(), min_reps: self.min_reps, init: self.init.clone(), folder: self.folder.clone()}
   }
+}
+
 
+/// A Parser that works like `RepSepParser`, but collects the `rep` results
+/// into a container instead of a vector
+pub struct RepSepCollectParser<A, B, C> {
+  pub rep: A,
+  pub sep: B,
+  pub min_reps: usize,
+  _marker: PhantomData<C>
+}
+
+impl<I: ?Sized, A: Parser<I=I>, B: Parser<I=I>, C: Default + Extend<A::O>> Parser for RepSepCollectParser<A, B, C> {
+  type I = I;
+  type O = C;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    fold_repsep(&self.rep, &self.sep, self.min_reps, data, C::default(), |mut c, result| {
+      c.extend(Some(result));
+      c
+    })
+  }
+}
+
+impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>, C: Default + Extend<A::O>> ParserCombinator for RepSepCollectParser<A, B, C> {}
+
+impl<I: ?Sized, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>, C> Clone for RepSepCollectParser<A, B, C> {
+  fn clone(&self) -> Self {
+    RepSepCollectParser{rep: self.rep.clone(), sep: self.sep.clone(), min_reps: self.min_reps, _marker: PhantomData}
+  }
 }
 
 
@@ -407,12 +918,12 @@ impl<T: Parser> Parser for OneOfParser<T> {
 
   fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
     for p in self.options.iter() {
-      let r = p.parse(data.clone());
+      let r = p.parse(data);
       if r.is_ok() {
         return r;
       }
     }
-    Err(format!("All options failed"))
+    Err("All options failed".to_string())
   }
 
 }
@@ -420,13 +931,46 @@ impl<T: Parser> Parser for OneOfParser<T> {
 impl<T: ParserCombinator> ParserCombinator for OneOfParser<T> {}
 
 
+/// A Parser that takes a vector of parsers (of the exact same type), runs all
+/// of them, and returns the value from the one that consumed the most input.
+/// Ties go to the parser that appears first.
+#[derive(Clone)]
+pub struct LongestParser<T: Parser> {
+  options: Vec<T>
+}
+
+impl<T: Parser> Parser for LongestParser<T> where T::I: Input {
+  type I = T::I;
+  type O = T::O;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    let mut best: Option<(T::O, &'a T::I)> = None;
+    for p in self.options.iter() {
+      if let Ok((result, rest)) = p.parse(data) {
+        let better = match best {
+          Some((_, best_rest)) => rest.input_len() < best_rest.input_len(),
+          None => true,
+        };
+        if better {
+          best = Some((result, rest));
+        }
+      }
+    }
+    best.ok_or("All options failed".to_string())
+  }
+
+}
+
+impl<T: ParserCombinator> ParserCombinator for LongestParser<T> where T::I: Input {}
+
+
 /// this parser solely exists to avoid insanely long compile times in rustc.
 /// When you have a fairly large parser, it's best to box it.  Yes we're
 /// introducing extra dynamic dispatch, but only on a small amount.  In some
 /// cases this is the only way to get rustc to not take (literally) a million
 /// years!
 pub struct BoxedParser<I:?Sized,O> {
-  parser: Rc<Box<Parser<I=I,O=O>>>
+  parser: Rc<Box<dyn Parser<I=I,O=O>>>
 }
 
 impl<I:?Sized, O> Parser for BoxedParser<I, O> {
@@ -447,3 +991,177 @@ impl<I: ?Sized, O> Clone for BoxedParser<I, O>  {
     BoxedParser{parser: self.parser.clone()}
   }
 }
+
+
+/// A single member of a `PermutationParser`.  Every parser is a required
+/// member, and `OptionalMember` makes a parser optional.
+pub trait PermutationMember {
+  type I: ?Sized;
+  /// The value produced when the member matches
+  type Slot;
+  /// The value placed in the permutation's output tuple
+  type O;
+
+  fn parse_slot<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::Slot>;
+
+  /// Turn the (possibly missing) matched value into the final output
+  fn finish(&self, slot: Option<Self::Slot>, index: usize) -> Result<Self::O, String>;
+}
+
+impl<P: Parser> PermutationMember for P {
+  type I = P::I;
+  type Slot = P::O;
+  type O = P::O;
+
+  fn parse_slot<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::Slot> {
+    self.parse(data)
+  }
+
+  fn finish(&self, slot: Option<Self::Slot>, index: usize) -> Result<Self::O, String> {
+    slot.ok_or(format!("Missing required permutation member {}", index))
+  }
+}
+
+/// A permutation member that is allowed to be absent from the input
+#[derive(Clone)]
+pub struct OptionalMember<P: Parser> {
+  parser: P
+}
+
+impl<P: Parser> PermutationMember for OptionalMember<P> {
+  type I = P::I;
+  type Slot = P::O;
+  type O = Option<P::O>;
+
+  fn parse_slot<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::Slot> {
+    self.parser.parse(data)
+  }
+
+  fn finish(&self, slot: Option<Self::Slot>, _: usize) -> Result<Self::O, String> {
+    Ok(slot)
+  }
+}
+
+/// A tuple of permutation members.  This is implemented for tuples of up to 8
+/// `PermutationMember`s that all share the same input type.
+pub trait PermutationMembers {
+  type I: ?Sized;
+  type O;
+
+  fn parse_members<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O>;
+}
+
+macro_rules! permutation_members {
+  ($($member:ident $idx:tt),+) => {
+    impl<In: ?Sized + Input, $($member: PermutationMember<I=In>),+> PermutationMembers for ($($member,)+) {
+      type I = In;
+      type O = ($($member::O,)+);
+
+      fn parse_members<'a>(&self, data: &'a In) -> ParseResult<&'a In, Self::O> {
+        let mut slots = ($(None::<<$member as PermutationMember>::Slot>,)+);
+        let mut remain = data;
+        'members: loop {
+          $(
+            if slots.$idx.is_none() {
+              if let Ok((result, rest)) = self.$idx.parse_slot(remain) {
+                if rest.input_len() < remain.input_len() {
+                  slots.$idx = Some(result);
+                  remain = rest;
+                  continue 'members;
+                }
+              }
+            }
+          )+
+          break;
+        }
+        //members that succeed without consuming anything only fill in what's left over
+        $(
+          if slots.$idx.is_none() {
+            if let Ok((result, _)) = self.$idx.parse_slot(remain) {
+              slots.$idx = Some(result);
+            }
+          }
+        )+
+        //a member that already matched can't come up again right after the permutation
+        $(
+          if slots.$idx.is_some() {
+            if let Ok((_, rest)) = self.$idx.parse_slot(remain) {
+              if rest.input_len() < remain.input_len() {
+                return Err(format!("Duplicate permutation member {}", $idx));
+              }
+            }
+          }
+        )+
+        Ok((($(self.$idx.finish(slots.$idx, $idx)?,)+), remain))
+      }
+    }
+  }
+}
+
+permutation_members!(A 0);
+permutation_members!(A 0, B 1);
+permutation_members!(A 0, B 1, C 2);
+permutation_members!(A 0, B 1, C 2, D 3);
+permutation_members!(A 0, B 1, C 2, D 3, E 4);
+permutation_members!(A 0, B 1, C 2, D 3, E 4, F 5);
+permutation_members!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+permutation_members!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// A Parser that matches a set of member parsers in any order, each at most
+/// once, and returns their results in declaration order.
+#[derive(Clone)]
+pub struct PermutationParser<P> {
+  members: P
+}
+
+impl<P: PermutationMembers> Parser for PermutationParser<P> {
+  type I = P::I;
+  type O = P::O;
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    self.members.parse_members(data)
+  }
+}
+
+impl<P: PermutationMembers + Clone> ParserCombinator for PermutationParser<P> {}
+
+
+/// A Parser that repeats `item` until `end` succeeds, returning the collected
+/// items along with the value from `end`
+#[derive(Clone)]
+pub struct ManyTillParser<A, B> {
+  item: A,
+  end: B,
+}
+
+impl<I: ?Sized + Input, A: Parser<I=I>, B: Parser<I=I>> Parser for ManyTillParser<A, B> {
+  type I = I;
+  type O = (Vec<A::O>, B::O);
+
+  fn parse<'a>(&self, data: &'a Self::I) -> ParseResult<&'a Self::I, Self::O> {
+    let mut remain = data;
+    let mut v: Vec<A::O> = Vec::new();
+    loop {
+      if let Ok((end, rest)) = self.end.parse(remain) {
+        return Ok(((v, end), rest));
+      }
+      if remain.input_len() == 0 {
+        return Err("Ran out of data before terminator".to_string());
+      }
+      match self.item.parse(remain) {
+        Ok((_, rest)) if rest.input_len() == remain.input_len() => {
+          return Err("Item parser did not consume any input".to_string());
+        }
+        Ok((result, rest)) => {
+          v.push(result);
+          remain = rest;
+        }
+        Err(err) => {
+          return Err(format!("Error on item: {}", err));
+        }
+      }
+    }
+  }
+}
+
+impl<I: ?Sized + Input, A: ParserCombinator<I=I>, B: ParserCombinator<I=I>> ParserCombinator for ManyTillParser<A, B> {}
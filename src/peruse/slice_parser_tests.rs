@@ -54,7 +54,7 @@ fn test_or() {
 
 #[test]
 fn test_recursive() {
-  fn recurse() -> Box<Parser<I=[i32], O=i32>> {
+  fn recurse() -> Box<dyn Parser<I=[i32], O=i32>> {
     let end = lit(1).map(|_| 0);
     let rec = lit(0).then_r(recursive(|| recurse())).map(|t| t + 1);
     Box::new(end.or(rec))
@@ -176,7 +176,7 @@ fn basic_example() {
   }
 
 
-  fn expression() -> Box<Parser<I=[Token], O=Expression>> {
+  fn expression() -> Box<dyn Parser<I=[Token], O=Expression>> {
 
     let paren = lit(Token::OpenParen).then_r(recursive(|| expression())).then_l(lit(Token::CloseParen));
 
@@ -254,3 +254,221 @@ fn test_oneof() {
   assert_eq!(p.repeat().parse(&input), Ok((vec![3, 1, 9, 11, 27], &input[5..])));
 
 }
+
+#[test]
+fn test_longest() {
+  let short = boxed(lit(1).map(|_| 1));
+  let long = boxed(lit(1).then(lit(2)).map(|_| 2));
+  let parser = longest(vec![short.clone(), long.clone()]);
+  let input = [1, 2, 3];
+  assert_eq!(parser.parse(&input), Ok((2, &input[2..])));
+
+  //ties go to the first parser
+  let tied = longest(vec![short, boxed(lit(1).map(|_| 3)), long]);
+  let input2 = [1, 3];
+  assert_eq!(tied.parse(&input2), Ok((1, &input2[1..])));
+
+  assert!(tied.parse(&[4]).is_err());
+}
+
+#[test]
+fn test_permutation() {
+  let parser = permutation((lit(1), optional(lit(2).map(|_| "two")), lit(3).then(lit(4))));
+
+  let input = [3, 4, 2, 1, 5];
+  assert_eq!(parser.parse(&input), Ok(((1, Some("two"), (3, 4)), &input[4..])));
+
+  let input2 = [1, 3, 4];
+  assert_eq!(parser.parse(&input2), Ok(((1, None, (3, 4)), &input2[3..])));
+
+  assert_eq!(parser.parse(&[1, 1, 3, 4]), Err("Duplicate permutation member 0".to_string()));
+  assert_eq!(parser.parse(&[2, 1]), Err("Missing required permutation member 2".to_string()));
+}
+
+#[test]
+fn test_permutation_without_progress() {
+  let parser = permutation((opt(lit(1)), lit(2)));
+  assert_eq!(parser.parse(&[2]), Ok(((None, 2), &[][..])));
+  assert_eq!(parser.parse(&[1, 2]), Ok(((Some(1), 2), &[][..])));
+  assert_eq!(parser.parse(&[2, 1]), Ok(((Some(1), 2), &[][..])));
+
+  let input = [1, 2, 5];
+  let parser2 = permutation((lit(1), take_while(|i: &i32| *i < 3)));
+  assert_eq!(parser2.parse(&input), Ok(((1, vec![2]), &input[2..])));
+  assert_eq!(parser2.parse(&input[..1]), Ok(((1, vec![]), &input[1..1])));
+  assert_eq!(parser2.parse(&[1, 5]), Ok(((1, vec![]), &[5][..])));
+
+  let parser3 = permutation((succeed(7), lit(1)));
+  assert_eq!(parser3.parse(&[1]), Ok(((7, 1), &[][..])));
+
+  let input2 = [1, 2, 0, 2, 1, 3];
+  let groups = repsep(permutation((lit(1), lit(2))), lit(0));
+  assert_eq!(groups.parse(&input2), Ok((vec![(1, 2), (1, 2)], &input2[5..])));
+}
+
+#[test]
+fn test_permutation_duplicate_after_complete() {
+  let optional_member = permutation((lit(1), optional(lit(2))));
+  assert_eq!(optional_member.parse(&[1, 1, 2]), Err("Duplicate permutation member 0".to_string()));
+  assert_eq!(optional_member.parse(&[1, 2, 1]), Err("Duplicate permutation member 0".to_string()));
+
+  let required = permutation((lit(1), lit(2)));
+  assert_eq!(required.parse(&[1, 2, 1]), Err("Duplicate permutation member 0".to_string()));
+  assert_eq!(required.parse(&[2, 1, 2]), Err("Duplicate permutation member 1".to_string()));
+  assert_eq!(required.parse(&[2, 1, 3]), Ok(((1, 2), &[3][..])));
+}
+
+#[test]
+fn test_fold() {
+  let parser = lit(1).then(lit(0)).fold(0, |count, _| count + 1);
+  let input = [1, 0, 1, 0, 1, 0, 2];
+  assert_eq!(parser.parse(&input), Ok((3, &input[6..])));
+  assert_eq!(parser.parse(&input[6..]), Ok((0, &input[6..])));
+}
+
+#[test]
+fn test_collect() {
+  use std::collections::HashMap;
+  let parser = matcher(|i: i32| if i > 0 {Some(i)} else {None}).then(lit(0)).collect::<HashMap<i32, i32>>();
+  let input = [1, 0, 2, 0, 1, 0, 5];
+  let (map, rest) = parser.parse(&input).unwrap();
+  assert_eq!(map.len(), 2);
+  assert_eq!(map.get(&1), Some(&0));
+  assert_eq!(rest, &input[6..]);
+}
+
+#[test]
+fn test_repsep_fold() {
+  let parser = repsep_fold(lit(1).or(lit(2)), lit(0), 0, |sum, i| sum + i);
+  let input = [1, 0, 2, 0, 2, 3];
+  assert_eq!(parser.parse(&input), Ok((5, &input[5..])));
+
+  let bad_input = [1, 0, 3];
+  assert!(parser.parse(&bad_input).is_err());
+}
+
+#[test]
+fn test_many_till() {
+  let parser = many_till(lit(1), lit(0).then(lit(0))).map(|(v, _)| v.len());
+  let input = [1, 1, 0, 0, 2];
+  assert_eq!(parser.parse(&input), Ok((2, &input[4..])));
+  assert_eq!(parser.parse(&input[2..]), Ok((0, &input[4..])));
+
+  assert_eq!(parser.parse(&[1, 1]), Err("Ran out of data before terminator".to_string()));
+  assert!(parser.parse(&[1, 2, 0, 0]).is_err());
+
+  let no_progress = many_till(opt(lit(1)), lit(0));
+  assert_eq!(no_progress.parse(&[1, 0]), Ok(((vec![Some(1)], 0), &[][..])));
+  assert_eq!(no_progress.parse(&[2, 0]), Err("Item parser did not consume any input".to_string()));
+}
+
+#[test]
+fn test_take_until() {
+  let parser = take_until(lit(0));
+  let input = [3, 2, 0, 1];
+  assert_eq!(parser.parse(&input), Ok((vec![3, 2], &input[2..])));
+  assert!(parser.parse(&[3, 2]).is_err());
+}
+
+#[test]
+fn test_verify() {
+  let parser = matcher(Some).verify(|i: &i32| *i < 256, "value out of range");
+  let input = [255, 256];
+  assert_eq!(parser.parse(&input), Ok((255, &input[1..])));
+  assert_eq!(parser.parse(&input[1..]), Err("value out of range".to_string()));
+  assert_eq!(parser.repeat().parse(&input), Ok((vec![255], &input[1..])));
+}
+
+#[test]
+fn test_any() {
+  let parser = any().then(any());
+  let input = ["a", "b", "c"];
+  assert_eq!(parser.parse(&input), Ok((("a", "b"), &input[2..])));
+  assert!(parser.parse(&input[2..]).is_err());
+}
+
+#[test]
+fn test_take() {
+  //a length-prefixed byte buffer
+  let parser = any().then(take(2)).then(any());
+  let input: [u8; 4] = [3, 0xCA, 0xFE, 9];
+  assert_eq!(parser.parse(&input), Ok((((3, vec![0xCA, 0xFE]), 9), &input[4..])));
+  assert!(take::<u8>(5).parse(&input).is_err());
+}
+
+#[test]
+fn test_take_while() {
+  let parser = take_while(|b: &u8| b.is_ascii_digit());
+  let input = *b"123ab";
+  assert_eq!(parser.parse(&input), Ok((b"123".to_vec(), &input[3..])));
+  assert_eq!(parser.parse(&input[3..]), Ok((vec![], &input[3..])));
+}
+
+#[test]
+fn test_skip_while() {
+  let parser = skip_while(|i: &i32| *i == 0).then_r(lit(1)).repeat();
+  let input = [0, 0, 1, 1, 0, 1, 2];
+  assert_eq!(parser.parse(&input), Ok((vec![1, 1, 1], &input[6..])));
+}
+
+#[test]
+fn test_tag() {
+  let parser = tag(&[1, 2, 3]).then(lit(4));
+  let input = [1, 2, 3, 4, 5];
+  assert_eq!(parser.parse(&input), Ok(((vec![1, 2, 3], 4), &input[4..])));
+  assert_eq!(parser.parse(&[1, 3, 3, 4]), Err("Tag mismatch at element 1 of 3".to_string()));
+  assert_eq!(parser.parse(&[1, 2]), Err("ran out of data at element 2 of 3 in tag".to_string()));
+}
+
+#[test]
+fn test_item_sets() {
+  use std::collections::BTreeSet;
+  let set: BTreeSet<i32> = vec![1, 3, 5].into_iter().collect();
+  let odd = one_of_items(set.clone());
+  let even = none_of_items(set);
+  let input = [3, 4, 5];
+  assert_eq!(odd.parse(&input), Ok((3, &input[1..])));
+  assert_eq!(odd.parse(&input[1..]), Err("Expected one of {1, 3, 5}".to_string()));
+  assert_eq!(even.parse(&input[1..]), Ok((4, &input[2..])));
+  assert_eq!(even.parse(&input[2..]), Err("Expected none of {1, 3, 5}".to_string()));
+
+  let sorted_set = one_of_items(sorted(&[5, 1, 3, 1]));
+  assert_eq!(sorted_set.repeat().parse(&[1, 5, 2]), Ok((vec![1, 5], &[2][..])));
+}
+
+#[test]
+fn test_variant() {
+  #[derive(Debug, Clone, Eq, PartialEq)]
+  enum Token {
+    PlusSign,
+    Ident(String),
+    Term(i32)
+  }
+  let sum = variant(Token::Term(0)).then_l(lit(Token::PlusSign)).then(variant(Token::Term(0)));
+  let input = [Token::Term(1), Token::PlusSign, Token::Term(2), Token::Ident("x".to_string())];
+  assert_eq!(sum.parse(&input), Ok(((Token::Term(1), Token::Term(2)), &input[3..])));
+
+  let ident = variant(Token::Ident(String::new()));
+  assert_eq!(ident.parse(&input[3..]), Ok((Token::Ident("x".to_string()), &input[4..])));
+  assert_eq!(ident.parse(&input), Err("Variant mismatch".to_string()));
+}
+
+#[test]
+fn test_ref_matcher() {
+  //no Clone on purpose
+  #[derive(Debug, PartialEq, Eq)]
+  enum Token {
+    Ident(String),
+    Comma,
+  }
+  let ident = ref_matcher(|t: &Token| match *t {
+    Token::Ident(ref name) => Some(name.clone()),
+    _ => None
+  });
+  let parser = repsep(ident, skip_lit(Token::Comma));
+  let input = [Token::Ident("a".to_string()), Token::Comma, Token::Ident("b".to_string())];
+  let (names, rest) = parser.parse(&input).unwrap();
+  assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+  assert!(rest.is_empty());
+  assert!(skip_lit(Token::Comma).parse(&input).is_err());
+}
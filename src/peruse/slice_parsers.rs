@@ -4,9 +4,13 @@
 
 use std::rc::Rc;
 use std::marker::PhantomData;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::mem::{discriminant, Discriminant};
 use parsers::{Parser, ParserCombinator, ParseResult};
 
-pub type SliceParser<I,O> = Parser<I=[I], O=O>;
+pub type SliceParser<I,O> = dyn Parser<I=[I], O=O>;
 
 /// Create a parser that only recognizes the given literal value
 ///
@@ -26,6 +30,91 @@ pub fn lit<T: Eq + Clone>(l: T) -> LiteralParser<T> {
   LiteralParser{literal: l}
 }
 
+/// Create a parser that recognizes any element of the same enum variant as the given example,
+/// ignoring the variant's fields.  The matched element is returned.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Token {
+///   Plus,
+///   Term(i32),
+/// }
+/// let input = [Token::Term(4), Token::Plus];
+/// let term = variant(Token::Term(0));
+/// assert_eq!(term.parse(&input), Ok((Token::Term(4), &input[1..])));
+/// assert!(term.parse(&input[1..]).is_err());
+/// ```
+///
+pub fn variant<T: Clone>(example: T) -> VariantParser<T> {
+  VariantParser{discriminant: discriminant(&example)}
+}
+
+/// Create a parser that recognizes an exact sequence of elements at the beginning of the slice and
+/// returns a copy of the matched elements.  On a mismatch, the error reports the index within the
+/// sequence where matching failed.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::tag;
+/// let input: [u8; 5] = [0xCA, 0xFE, 0xBA, 0xBE, 0x01];
+/// let magic = tag(&[0xCA, 0xFE, 0xBA, 0xBE]);
+/// assert_eq!(magic.parse(&input), Ok((vec![0xCA, 0xFE, 0xBA, 0xBE], &input[4..])));
+///
+/// let bad_input: [u8; 4] = [0xCA, 0xFE, 0xBB, 0xBE];
+/// assert_eq!(magic.parse(&bad_input), Err("Tag mismatch at element 2 of 4".to_string()));
+/// ```
+///
+pub fn tag<T: Eq + Clone>(t: &[T]) -> TagParser<T> {
+  TagParser{tag: t.to_vec()}
+}
+
+/// Create a parser that accepts the first element of the slice if it is a member of the given set.
+/// Any `ItemSet` can be used, such as a `HashSet`, a `BTreeSet` or a `SortedItems`.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let ops = one_of_items(sorted(&['+', '-', '*', '/']));
+/// let input = ['*', 'x'];
+/// assert_eq!(ops.parse(&input), Ok(('*', &input[1..])));
+/// assert_eq!(ops.parse(&input[1..]), Err("Expected one of ['*', '+', '-', '/']".to_string()));
+/// ```
+///
+pub fn one_of_items<T: Clone, S: ItemSet<T> + Debug>(set: S) -> ItemSetParser<T, S> {
+  ItemSetParser{set: Rc::new(set), member: true, _marker: PhantomData}
+}
+
+/// Create a parser that accepts the first element of the slice if it is *not* a member of the
+/// given set.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// # use std::collections::HashSet;
+/// let delimiters: HashSet<char> = [',', ';'].iter().cloned().collect();
+/// let field = none_of_items(delimiters).repeat();
+/// let input = ['a', 'b', ',', 'c'];
+/// assert_eq!(field.parse(&input), Ok((vec!['a', 'b'], &input[2..])));
+/// ```
+///
+pub fn none_of_items<T: Clone, S: ItemSet<T> + Debug>(set: S) -> ItemSetParser<T, S> {
+  ItemSetParser{set: Rc::new(set), member: false, _marker: PhantomData}
+}
+
+/// Build a `SortedItems` set from a slice of items, for use with `one_of_items` and `none_of_items`
+pub fn sorted<T: Ord + Clone>(items: &[T]) -> SortedItems<T> {
+  let mut v = items.to_vec();
+  v.sort();
+  v.dedup();
+  SortedItems{items: v}
+}
+
 /// Create a parser that matches against the first element in the slice and returns either a Some
 /// of an output value, or None if the element does not match.  If None is returned the parser will
 /// return an error.
@@ -46,11 +135,170 @@ pub fn matcher<T: Clone, U, F: 'static + Fn(T) -> Option<U>>(f: F) -> MatchParse
   MatchParser{matcher: Rc::new(f), _marker: PhantomData}
 }
 
+/// Create a parser like `matcher`, except the closure receives a reference to the first element
+/// instead of a clone of it.  The closure decides what (if anything) to clone into the output, so
+/// the elements don't need to implement `Clone`.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// enum Token {
+///   Ident(String),
+///   Comma,
+/// }
+/// let ident = ref_matcher(|t: &Token| match *t {
+///   Token::Ident(ref name) => Some(name.len()),
+///   _ => None
+/// });
+/// let input = [Token::Ident("abc".to_string()), Token::Comma];
+/// assert_eq!(ident.parse(&input).map(|(len, rest)| (len, rest.len())), Ok((3, 1)));
+/// ```
+///
+pub fn ref_matcher<T, U, F: 'static + Fn(&T) -> Option<U>>(f: F) -> RefMatchParser<T, U, F> {
+  RefMatchParser{matcher: Rc::new(f), _marker: PhantomData}
+}
+
+/// Create a parser that recognizes the given literal value like `lit`, but returns `()` instead of
+/// a clone of the matched element.  Useful for punctuation tokens whose value is thrown away anyway.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let input = [0, 5, 0];
+/// let parser = skip_lit(0).then_r(lit(5)).then_l(skip_lit(0));
+/// assert_eq!(parser.parse(&input), Ok((5, &input[3..])));
+/// ```
+///
+pub fn skip_lit<T: Eq>(l: T) -> SkipLiteralParser<T> {
+  SkipLiteralParser{literal: Rc::new(l)}
+}
+
+/// Create a parser that consumes and returns any single element.  It only fails if the slice is
+/// empty.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let input = [7, 8];
+/// assert_eq!(any().parse(&input), Ok((7, &input[1..])));
+/// assert!(any::<i32>().parse(&[]).is_err());
+/// ```
+///
+pub fn any<T: Clone>() -> AnyParser<T> {
+  AnyParser{_marker: PhantomData}
+}
+
+/// Create a parser that consumes exactly `n` elements and returns them.  It fails if fewer than
+/// `n` elements remain.
+///
+/// Parsers can't return references into the input, so the elements are copied into a `Vec`.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let input = [1, 2, 3];
+/// assert_eq!(take(2).parse(&input), Ok((vec![1, 2], &input[2..])));
+/// assert!(take(4).parse(&input).is_err());
+/// ```
+///
+pub fn take<T: Clone>(n: usize) -> TakeParser<T> {
+  TakeParser{count: n, _marker: PhantomData}
+}
+
+/// Create a parser that consumes elements for as long as they satisfy the given predicate and
+/// returns them.  This never fails; if the first element doesn't match an empty `Vec` is returned.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let input = [1, 2, 5, 1];
+/// let parser = take_while(|i: &i32| *i < 4);
+/// assert_eq!(parser.parse(&input), Ok((vec![1, 2], &input[2..])));
+/// ```
+///
+pub fn take_while<T: Clone, F: Fn(&T) -> bool>(f: F) -> TakeWhileParser<T, F> {
+  TakeWhileParser{predicate: Rc::new(f), _marker: PhantomData}
+}
+
+/// Create a parser that skips elements for as long as they satisfy the given predicate.  Like
+/// `take_while` this never fails, but nothing is copied.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let input = [0, 0, 3, 0];
+/// let parser = skip_while(|i: &i32| *i == 0).then_r(lit(3));
+/// assert_eq!(parser.parse(&input), Ok((3, &input[3..])));
+/// ```
+///
+pub fn skip_while<T, F: Fn(&T) -> bool>(f: F) -> SkipWhileParser<T, F> {
+  SkipWhileParser{predicate: Rc::new(f), _marker: PhantomData}
+}
+
+/// Create a parser that consumes elements until the `end` parser matches, returning the consumed
+/// elements.  The elements matched by `end` are not consumed.  If `end` never matches, the parser
+/// fails.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// let input = [5, 6, 0, 0, 7];
+/// let parser = take_until(lit(0).then(lit(0)));
+/// assert_eq!(parser.parse(&input), Ok((vec![5, 6], &input[2..])));
+/// ```
+///
+pub fn take_until<T: Clone, P: Parser<I=[T]>>(end: P) -> TakeUntilParser<P> {
+  TakeUntilParser{end}
+}
 
 
 //////////////////////// STRUCTS /////////////////////////////////////////////
 
 
+/// A set of items that can be checked for membership by `ItemSetParser`
+pub trait ItemSet<T> {
+  fn contains_item(&self, item: &T) -> bool;
+}
+
+impl<T: Eq + Hash> ItemSet<T> for HashSet<T> {
+  fn contains_item(&self, item: &T) -> bool {
+    self.contains(item)
+  }
+}
+
+impl<T: Ord> ItemSet<T> for BTreeSet<T> {
+  fn contains_item(&self, item: &T) -> bool {
+    self.contains(item)
+  }
+}
+
+/// A sorted vector of items that is searched with a binary search.  Use `sorted` to create one.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SortedItems<T: Ord> {
+  items: Vec<T>
+}
+
+impl<T: Ord> ItemSet<T> for SortedItems<T> {
+  fn contains_item(&self, item: &T) -> bool {
+    self.items.binary_search(item).is_ok()
+  }
+}
+
+impl<T: Ord + Debug> Debug for SortedItems<T> {
+  fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+    self.items.fmt(f)
+  }
+}
+
+
+
 
 /// A LiteralParser looks for an exact match of the given item at the beginning
 // of the slice
@@ -64,13 +312,13 @@ impl<T: Eq + Clone> Parser for LiteralParser< T> {
   type O = T;
 
   fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], T> {
-    if data.len() < 1 {
-      return Err(format!("ran out of data"))
+    if data.is_empty() {
+      return Err("ran out of data".to_string())
     }
     if data[0] == self.literal {
       Ok((data[0].clone(), &data[1..]))
     } else {
-      Err(format!("Literal mismatch"))
+      Err("Literal mismatch".to_string())
     }
   }
 }
@@ -79,6 +327,125 @@ impl<T: Eq + Clone> ParserCombinator for LiteralParser<T>{}
 
 
 
+/// A SkipLiteralParser looks for an exact match of the given item at the beginning of the slice
+/// and discards it
+pub struct SkipLiteralParser<T: Eq> {
+  literal: Rc<T>,
+}
+
+impl<T: Eq> Parser for SkipLiteralParser<T> {
+  type I = [T];
+  type O = ();
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], ()> {
+    match data.first() {
+      Some(t) if *t == *self.literal => Ok(((), &data[1..])),
+      Some(_) => Err("Literal mismatch".to_string()),
+      None    => Err("ran out of data".to_string())
+    }
+  }
+}
+
+impl<T: Eq> ParserCombinator for SkipLiteralParser<T> {}
+
+impl<T: Eq> Clone for SkipLiteralParser<T> {
+  fn clone(&self) -> Self {
+    SkipLiteralParser{literal: self.literal.clone()}
+  }
+}
+
+
+/// A VariantParser looks for an element with the same enum variant as the one it was created with
+pub struct VariantParser<T> {
+  discriminant: Discriminant<T>,
+}
+
+impl<T: Clone> Parser for VariantParser<T> {
+  type I = [T];
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], T> {
+    if data.is_empty() {
+      return Err("ran out of data".to_string())
+    }
+    if discriminant(&data[0]) == self.discriminant {
+      Ok((data[0].clone(), &data[1..]))
+    } else {
+      Err("Variant mismatch".to_string())
+    }
+  }
+}
+
+impl<T: Clone> ParserCombinator for VariantParser<T> {}
+
+impl<T> Clone for VariantParser<T> {
+  fn clone(&self) -> Self {
+    VariantParser{discriminant: self.discriminant}
+  }
+}
+
+
+/// A TagParser looks for an exact match of a sequence of items at the beginning of the slice
+#[derive(Clone)]
+pub struct TagParser<T: Eq + Clone> {
+  pub tag: Vec<T>,
+}
+
+impl<T: Eq + Clone> Parser for TagParser<T> {
+  type I = [T];
+  type O = Vec<T>;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], Self::O> {
+    for (i, t) in self.tag.iter().enumerate() {
+      match data.get(i) {
+        Some(d) if d == t => {},
+        Some(_) => return Err(format!("Tag mismatch at element {} of {}", i, self.tag.len())),
+        None    => return Err(format!("ran out of data at element {} of {} in tag", i, self.tag.len())),
+      }
+    }
+    Ok((self.tag.clone(), &data[self.tag.len()..]))
+  }
+}
+
+impl<T: Eq + Clone> ParserCombinator for TagParser<T> {}
+
+
+
+/// An ItemSetParser accepts the first element of the slice based on whether it is a member of a set
+pub struct ItemSetParser<T: Clone, S: ItemSet<T> + Debug> {
+  set: Rc<S>,
+  member: bool,
+  _marker: PhantomData<T>
+}
+
+impl<T: Clone, S: ItemSet<T> + Debug> Parser for ItemSetParser<T, S> {
+  type I = [T];
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], T> {
+    if data.is_empty() {
+      return Err("ran out of data".to_string())
+    }
+    if self.set.contains_item(&data[0]) == self.member {
+      Ok((data[0].clone(), &data[1..]))
+    } else if self.member {
+      Err(format!("Expected one of {:?}", self.set))
+    } else {
+      Err(format!("Expected none of {:?}", self.set))
+    }
+  }
+}
+
+impl<T: Clone, S: ItemSet<T> + Debug> ParserCombinator for ItemSetParser<T, S> {}
+
+impl<T: Clone, S: ItemSet<T> + Debug> Clone for ItemSetParser<T, S> {
+  fn clone(&self) -> Self {
+    ItemSetParser{set: self.set.clone(), member: self.member, _marker: PhantomData}
+  }
+}
+
+
+
 pub struct MatchParser<T: Clone, U, F: Fn(T) -> Option<U>> {
   matcher: Rc<F>,
   _marker: PhantomData<T>
@@ -89,12 +456,12 @@ impl<T: Clone, U, F: Fn(T) -> Option<U>> Parser for MatchParser<T,U, F> {
   type O = U;
 
   fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], Self::O> {
-    if data.len() < 1 {
-      return Err(format!("ran out of data"))
+    if data.is_empty() {
+      return Err("ran out of data".to_string())
     }
     match (self.matcher)(data[0].clone()) {
       Some(u) => Ok((u, &data[1..])),
-      None    => Err(format!("Match failed"))
+      None    => Err("Match failed".to_string())
     }
   }
 }
@@ -110,4 +477,158 @@ impl<T: Clone, U, F: Fn(T) -> Option<U>> Clone for MatchParser<T,U, F> {
 
 }
 
-  
+
+/// A TakeUntilParser consumes elements up to the first position where its `end` parser matches
+#[derive(Clone)]
+pub struct TakeUntilParser<P> {
+  end: P,
+}
+
+impl<T: Clone, P: Parser<I=[T]>> Parser for TakeUntilParser<P> {
+  type I = [T];
+  type O = Vec<T>;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], Self::O> {
+    for i in 0..(data.len() + 1) {
+      if self.end.parse(&data[i..]).is_ok() {
+        return Ok((data[..i].to_vec(), &data[i..]))
+      }
+    }
+    Err("ran out of data before terminator".to_string())
+  }
+}
+
+impl<T: Clone, P: ParserCombinator<I=[T]>> ParserCombinator for TakeUntilParser<P> {}
+
+
+/// An AnyParser consumes and returns the first element of the slice
+pub struct AnyParser<T: Clone> {
+  _marker: PhantomData<T>
+}
+
+impl<T: Clone> Parser for AnyParser<T> {
+  type I = [T];
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], T> {
+    match data.first() {
+      Some(t) => Ok((t.clone(), &data[1..])),
+      None    => Err("ran out of data".to_string())
+    }
+  }
+}
+
+impl<T: Clone> ParserCombinator for AnyParser<T> {}
+
+impl<T: Clone> Clone for AnyParser<T> {
+  fn clone(&self) -> Self {
+    AnyParser{_marker: PhantomData}
+  }
+}
+
+
+/// A TakeParser consumes and returns a fixed number of elements
+pub struct TakeParser<T: Clone> {
+  pub count: usize,
+  _marker: PhantomData<T>
+}
+
+impl<T: Clone> Parser for TakeParser<T> {
+  type I = [T];
+  type O = Vec<T>;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], Self::O> {
+    if data.len() < self.count {
+      return Err(format!("ran out of data: needed {} elements, got {}", self.count, data.len()))
+    }
+    Ok((data[..self.count].to_vec(), &data[self.count..]))
+  }
+}
+
+impl<T: Clone> ParserCombinator for TakeParser<T> {}
+
+impl<T: Clone> Clone for TakeParser<T> {
+  fn clone(&self) -> Self {
+    TakeParser{count: self.count, _marker: PhantomData}
+  }
+}
+
+
+/// A TakeWhileParser consumes and returns elements as long as they satisfy a predicate
+pub struct TakeWhileParser<T: Clone, F: Fn(&T) -> bool> {
+  predicate: Rc<F>,
+  _marker: PhantomData<T>
+}
+
+impl<T: Clone, F: Fn(&T) -> bool> Parser for TakeWhileParser<T, F> {
+  type I = [T];
+  type O = Vec<T>;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], Self::O> {
+    let n = data.iter().take_while(|t| (self.predicate)(t)).count();
+    Ok((data[..n].to_vec(), &data[n..]))
+  }
+}
+
+impl<T: Clone, F: Fn(&T) -> bool> ParserCombinator for TakeWhileParser<T, F> {}
+
+impl<T: Clone, F: Fn(&T) -> bool> Clone for TakeWhileParser<T, F> {
+  fn clone(&self) -> Self {
+    TakeWhileParser{predicate: self.predicate.clone(), _marker: PhantomData}
+  }
+}
+
+
+/// A SkipWhileParser consumes elements as long as they satisfy a predicate
+pub struct SkipWhileParser<T, F: Fn(&T) -> bool> {
+  predicate: Rc<F>,
+  _marker: PhantomData<T>
+}
+
+impl<T, F: Fn(&T) -> bool> Parser for SkipWhileParser<T, F> {
+  type I = [T];
+  type O = ();
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], ()> {
+    let n = data.iter().take_while(|t| (self.predicate)(t)).count();
+    Ok(((), &data[n..]))
+  }
+}
+
+impl<T, F: Fn(&T) -> bool> ParserCombinator for SkipWhileParser<T, F> {}
+
+impl<T, F: Fn(&T) -> bool> Clone for SkipWhileParser<T, F> {
+  fn clone(&self) -> Self {
+    SkipWhileParser{predicate: self.predicate.clone(), _marker: PhantomData}
+  }
+}
+
+
+/// A RefMatchParser works like a `MatchParser`, but gives its closure a reference to the element
+pub struct RefMatchParser<T, U, F: Fn(&T) -> Option<U>> {
+  matcher: Rc<F>,
+  _marker: PhantomData<T>
+}
+
+impl<T, U, F: Fn(&T) -> Option<U>> Parser for RefMatchParser<T, U, F> {
+  type I = [T];
+  type O = U;
+
+  fn parse<'a>(&self, data: &'a [T]) -> ParseResult<&'a [T], Self::O> {
+    match data.first() {
+      Some(t) => match (self.matcher)(t) {
+        Some(u) => Ok((u, &data[1..])),
+        None    => Err("Match failed".to_string())
+      },
+      None => Err("ran out of data".to_string())
+    }
+  }
+}
+
+impl<T, U, F: Fn(&T) -> Option<U>> ParserCombinator for RefMatchParser<T, U, F> {}
+
+impl<T, U, F: Fn(&T) -> Option<U>> Clone for RefMatchParser<T, U, F> {
+  fn clone(&self) -> Self {
+    RefMatchParser{matcher: self.matcher.clone(), _marker: PhantomData}
+  }
+}
@@ -15,3 +15,391 @@ fn test_captures() {
   let data = "34bah";
   assert_eq!(parser.parse(data), Ok((34, "bah")));
 }
+
+#[test]
+fn test_longest() {
+  let parser = longest(vec![str_lit("a", 1), str_lit("ab", 2), str_lit("b", 3)]);
+  assert_eq!(parser.parse("abc"), Ok((2, "c")));
+  assert_eq!(parser.parse("ac"), Ok((1, "c")));
+  assert!(parser.parse("c").is_err());
+}
+
+#[test]
+fn test_collect() {
+  let parser = str_lit("a", 'x').or(str_lit("b", 'y')).collect::<String>();
+  assert_eq!(parser.parse("abbac"), Ok(("xyyx".to_string(), "c")));
+}
+
+#[test]
+fn test_many_till() {
+  let parser = many_till(str_lit("a", 1).or(str_lit("b", 2)), str_lit(";", ()));
+  assert_eq!(parser.parse("abab;c"), Ok(((vec![1, 2, 1, 2], ()), "c")));
+  assert!(parser.parse("abab").is_err());
+}
+
+#[test]
+fn test_take_until() {
+  let comment = str_exact("/*", ()).then_r(str_take_until(str_exact("*/", ()))).then_l(str_exact("*/", ()));
+  assert_eq!(comment.parse("/* héllo * */x"), Ok((" héllo * ".to_string(), "x")));
+  assert!(comment.parse("/* héllo").is_err());
+}
+
+#[test]
+fn test_verify() {
+  let reserved = ["if", "while"];
+  let ident = capture(r"^[a-z]+", |caps| caps.at(0).unwrap().to_string())
+    .verify(move |s: &String| !reserved.contains(&s.as_str()), "reserved word");
+  assert_eq!(ident.parse("iffy x"), Ok(("iffy".to_string(), " x")));
+  assert_eq!(ident.parse("if x"), Err("reserved word".to_string()));
+}
+
+#[test]
+fn test_parse_iter() {
+  let line = str_take_until(str_lit("\n", ())).then_l(str_lit("\n", ()));
+  let data = "first\nsecond\nthird";
+  let mut iter = line.parse_iter(data);
+  assert_eq!(iter.next(), Some(Ok("first".to_string())));
+  assert_eq!(iter.remaining(), "second\nthird");
+  assert_eq!(iter.next(), Some(Ok("second".to_string())));
+  assert!(iter.next().unwrap().is_err());
+  assert_eq!(iter.next(), None);
+  assert_eq!(iter.remaining(), "third");
+
+  let words: Vec<_> = str_lit("a", 1).or(str_lit("b", 2)).parse_iter("abba").collect();
+  assert_eq!(words, vec![Ok(1), Ok(2), Ok(2), Ok(1)]);
+}
+
+#[test]
+fn test_pure_parsers() {
+  let sign = str_lit("-", -1).or(succeed(1));
+  assert_eq!(sign.parse("-5"), Ok((-1, "5")));
+  assert_eq!(sign.parse("5"), Ok((1, "5")));
+
+  let never = str_lit("a", 1).or(fail("expected an a"));
+  assert_eq!(never.parse("b"), Err("expected an a".to_string()));
+
+  let yes = str_lit("yes", ()).value(true);
+  assert_eq!(yes.parse("yes!"), Ok((true, "!")));
+
+  let allow_semicolon = true;
+  let stmt = str_lit("a", 'a').then_l(cond(allow_semicolon, str_lit(";", ())));
+  assert_eq!(stmt.parse("a;b"), Ok(('a', "b")));
+  let stmt2 = str_lit("a", 'a').then_l(cond(!allow_semicolon, str_lit(";", ())));
+  assert_eq!(stmt2.parse("a;b"), Ok(('a', ";b")));
+}
+
+#[test]
+fn test_exact() {
+  let parser = str_exact("(", "open").or(str_exact("+", "plus")).or(str_exact("**", "pow")).repeat();
+  assert_eq!(parser.parse("(+**(*"), Ok((vec!["open", "plus", "pow", "open"], "*")));
+  assert_eq!(str_exact("é", ()).parse("éa"), Ok(((), "a")));
+  assert_eq!(str_exact("ab", ()).parse("a"), Err("Expected \"ab\"".to_string()));
+}
+
+#[test]
+fn test_captures_anchored() {
+  let parser = capture(r"(\d+)", |caps| <i32>::from_str(caps.at(1).unwrap()).unwrap());
+  assert_eq!(parser.parse("ab34"), Err("No Match".to_string()));
+}
+
+#[test]
+fn test_literal_anchored() {
+  use regex::Regex;
+  assert!(rlit(Regex::new(r"\d+").unwrap(), ()).parse("ab34").is_err());
+  assert_eq!(rlit(Regex::new(r"\d+").unwrap(), ()).parse("34ab"), Ok(((), "ab")));
+
+  //every alternative is anchored, not just the first
+  let parser = str_lit("a|b", ());
+  assert!(parser.parse("cb").is_err());
+  assert_eq!(parser.parse("bc"), Ok(((), "c")));
+}
+
+#[test]
+fn test_literal_regex_options() {
+  use regex::RegexBuilder;
+  let regex = RegexBuilder::new("abc").case_insensitive(true).compile().unwrap();
+  assert_eq!(rlit(regex, 1).parse("ABCd"), Ok((1, "d")));
+}
+
+#[test]
+fn test_anchored_verbose_comment() {
+  let parser = capture(r"(?x) (\d+)  # digits", |caps| <i32>::from_str(caps.at(1).unwrap()).unwrap());
+  assert_eq!(parser.parse("42 rest"), Ok((42, " rest")));
+  assert!(parser.parse("x42").is_err());
+  assert_eq!(str_lit("(?x) a | b  # either", ()).parse("bc"), Ok(((), "c")));
+  assert_eq!(str_lit("(?i)ab", ()).parse("aBc"), Ok(((), "c")));
+}
+
+#[test]
+fn test_try_constructors() {
+  let parser = try_capture(r"(\d+)", |caps| <i32>::from_str(caps.at(1).unwrap()).unwrap()).unwrap();
+  assert_eq!(parser.parse("12a"), Ok((12, "a")));
+  assert!(try_capture(r"(\d+", |_| ()).is_err());
+
+  assert_eq!(try_str_lit("a+", 1).unwrap().parse("aab"), Ok((1, "b")));
+  assert!(try_str_lit("a{2", 1).is_err());
+}
+
+#[test]
+fn test_named_capture() {
+  use std::collections::HashMap;
+  let pair = named_capture::<HashMap<String, String>>(r"(?P<key>\w+)(=(?P<value>\w+))?");
+  let (map, rest) = pair.parse("debug;").unwrap();
+  assert_eq!(map.len(), 1);
+  assert_eq!(map["key"], "debug");
+  assert_eq!(rest, ";");
+  assert!(pair.parse(";").is_err());
+  assert!(try_named_capture::<HashMap<String, String>>(r"(?P<key").is_err());
+}
+
+#[test]
+fn test_char_parsers() {
+  let ident = alpha().then(alphanumeric().or(char_('_')).repeat());
+  assert_eq!(ident.parse("é_1 x"), Ok((('é', vec!['_', '1']), " x")));
+  assert!(ident.parse("1a").is_err());
+
+  assert_eq!(digit().repeat().parse("42a"), Ok((vec!['4', '2'], "a")));
+  assert_eq!(digit().parse("a"), Err("Expected a digit".to_string()));
+  assert_eq!(char_('x').parse("y"), Err("Expected 'x'".to_string()));
+  assert_eq!(whitespace().parse("\tx"), Ok(('\t', "x")));
+  assert_eq!(none_of_chars("\"\\").repeat().parse("ab\"c"), Ok((vec!['a', 'b'], "\"c")));
+  assert!(satisfy(|_| true).parse("").is_err());
+
+  let spaced = take_while_char(char::is_whitespace).then_r(one_of_chars("+-"));
+  assert_eq!(spaced.parse("  \u{3000}-1"), Ok(('-', "1")));
+  assert_eq!(take_while_char(char::is_whitespace).parse("abc"), Ok(("".to_string(), "abc")));
+}
+
+#[test]
+fn test_integers() {
+  assert_eq!(integer::<i8>().parse("-128,"), Ok((-128, ",")));
+  assert!(integer::<i8>().parse("128").unwrap_err().contains("\"128\""));
+  assert!(integer::<u16>().parse("-1").is_err());
+  assert_eq!(integer::<u64>().parse("18446744073709551615"), Ok((u64::MAX, "")));
+  assert!(integer::<u64>().parse("18446744073709551616").is_err());
+  assert_eq!(integer::<i32>().parse("12_3"), Ok((12, "_3")));
+  assert_eq!(integer::<i32>().with_separators().parse("1_2__3_ x"), Ok((123, " x")));
+  assert!(integer::<i32>().with_separators().parse("_1").is_err());
+}
+
+#[test]
+fn test_prefixed_integers() {
+  assert_eq!(hex_integer::<u8>().parse("0xfF"), Ok((255, "")));
+  assert!(hex_integer::<u8>().parse("0x100").is_err());
+  assert!(hex_integer::<u8>().parse("ff").is_err());
+  assert_eq!(octal_integer::<u32>().parse("0o17 "), Ok((15, " ")));
+  assert_eq!(binary_integer::<i16>().with_separators().parse("-0b1000_0000"), Ok((-128, "")));
+  assert_eq!(int_literal::<u32>().parse("0"), Ok((0, "")));
+  assert_eq!(int_literal::<u32>().parse("0X10"), Ok((16, "")));
+  assert!(int_literal::<u32>().parse("0xg").is_err());
+}
+
+#[test]
+fn test_floats() {
+  assert_eq!(float::<f64>().parse("1"), Ok((1.0, "")));
+  assert_eq!(float::<f64>().parse("-0.25x"), Ok((-0.25, "x")));
+  assert_eq!(float::<f64>().parse("+1E+3"), Ok((1000.0, "")));
+  assert_eq!(float::<f64>().parse("1e"), Ok((1.0, "e")));
+  assert_eq!(float::<f64>().with_separators().parse("1_000.000_5"), Ok((1000.0005, "")));
+  assert!(float::<f64>().parse(".5").is_err());
+  assert!(float::<f64>().parse("1e400").is_err());
+}
+
+#[test]
+fn test_quoted_json() {
+  let json = quoted_string(EscapeDialect::Json);
+  assert_eq!(json.parse(r#""\"\\\/\b\f\n\r\t""#), Ok(("\"\\/\u{8}\u{c}\n\r\t".to_string(), "")));
+  assert_eq!(json.parse(r#""\ud83d""#), Err("Unpaired surrogate `\\ud83d` at offset 1".to_string()));
+  assert_eq!(json.parse(r#""\ude00""#), Err("Unpaired surrogate `\\ude00` at offset 1".to_string()));
+  assert_eq!(json.parse(r#""ab\u12""#), Err("Invalid escape sequence `\\u12\"` at offset 3".to_string()));
+  assert_eq!(json.parse("\"a\nb\""), Err("Invalid character '\\n' in string literal at offset 2".to_string()));
+  assert_eq!(json.parse("\"abc"), Err("Unterminated string literal at offset 4".to_string()));
+  assert_eq!(json.parse("\"abc\\"), Err("Unterminated string literal at offset 5".to_string()));
+  assert_eq!(json.parse("\"é"), Err("Unterminated string literal at offset 3".to_string()));
+  assert!(json.parse("'abc'").is_err());
+  assert_eq!(json.parse("\"\\u12é\""), Err("Invalid escape sequence `\\u12é` at offset 1".to_string()));
+}
+
+#[test]
+fn test_quoted_rust() {
+  let rust = quoted_string(EscapeDialect::Rust).with_quotes("\"'");
+  assert_eq!(rust.parse(r#"'it\'s "fine"\0'"#), Ok(("it's \"fine\"\0".to_string(), "")));
+  assert_eq!(rust.parse("\"a\\\n    b\""), Ok(("ab".to_string(), "")));
+  assert_eq!(rust.parse(r#""\x80""#), Err("Invalid escape sequence `\\x80` at offset 1".to_string()));
+  assert_eq!(rust.parse(r#""\u{D800}""#), Err("Invalid escape sequence `\\u{D800}` at offset 1".to_string()));
+  assert!(rust.parse(r#""\u{1234567}""#).is_err());
+  assert!(rust.parse(r#""\u1234""#).is_err());
+  assert!(rust.parse("\"\\xé\"").is_err());
+}
+
+#[test]
+fn test_quoted_c() {
+  let c = quoted_string(EscapeDialect::C);
+  assert_eq!(c.parse(r#""\a\v\?\101\x42\u00e9\U0001F600""#), Ok(("\u{7}\u{b}?AB\u{e9}\u{1F600}".to_string(), "")));
+  assert_eq!(c.parse(r#""\0""#), Ok(("\0".to_string(), "")));
+  assert_eq!(c.parse(r#""\x""#), Err("Invalid escape sequence `\\x` at offset 1".to_string()));
+  assert!(c.parse("\"a\nb\"").is_err());
+  assert!(c.parse(r#""\/""#).is_err());
+}
+
+#[test]
+fn test_identifiers() {
+  let ascii = identifier(IdentifierStyle::Ascii);
+  assert_eq!(ascii.parse("_foo9 bar"), Ok(("_foo9".to_string(), " bar")));
+  assert_eq!(ascii.parse("naïve"), Ok(("na".to_string(), "ïve")));
+
+  let unicode = identifier(IdentifierStyle::Unicode).with_reserved(&["fn"]).with_reserved(&["let"]);
+  assert_eq!(unicode.parse("naïve+1"), Ok(("naïve".to_string(), "+1")));
+  assert_eq!(unicode.parse("日本語 x"), Ok(("日本語".to_string(), " x")));
+  assert_eq!(unicode.parse("let x"), Err("`let` is a reserved word".to_string()));
+  assert_eq!(unicode.parse("fn"), Err("`fn` is a reserved word".to_string()));
+  assert_eq!(unicode.parse("letter"), Ok(("letter".to_string(), "")));
+  assert!(unicode.parse("9x").is_err());
+}
+
+#[test]
+fn test_keywords() {
+  let stmt = keyword("while", "while").or(identifier(IdentifierStyle::Ascii).map(|_| "ident"));
+  assert_eq!(stmt.parse("while(x)"), Ok(("while", "(x)")));
+  assert_eq!(stmt.parse("whilex"), Ok(("ident", "")));
+  assert_eq!(keyword("if", ()).parse("if"), Ok(((), "")));
+
+  //non-ASCII letters only count as a boundary violation in the Unicode style
+  assert_eq!(keyword("if", ()).parse("ifé"), Ok(((), "é")));
+  assert!(keyword("if", ()).with_style(IdentifierStyle::Unicode).parse("ifé").is_err());
+}
+
+#[test]
+fn test_skipper() {
+  let trivia = skipper().whitespace().line_comment("#").block_comment("/*", "*/");
+  assert_eq!(trivia.parse("  # comment\n /* a /* b */ x"), Ok(((), "x")));
+  assert_eq!(trivia.parse("x "), Ok(((), "x ")));
+  assert_eq!(trivia.parse("  /* abc"), Err("Unterminated block comment at offset 2".to_string()));
+  assert_eq!(trivia.parse("# only a comment"), Ok(((), "")));
+
+  let nested = skipper().nested_block_comment("(*", "*)");
+  assert_eq!(nested.parse("(* a (* b *) c *)x"), Ok(((), "x")));
+  assert!(nested.parse("(* a (* b *)").is_err());
+}
+
+#[test]
+#[should_panic(expected = "Block comment delimiters can't be empty")]
+fn test_skipper_empty_block_comment() {
+  skipper().block_comment("", "*/");
+}
+
+#[test]
+#[should_panic(expected = "Line comment start can't be empty")]
+fn test_skipper_empty_line_comment() {
+  skipper().whitespace().line_comment("");
+}
+
+#[test]
+fn test_lexeme_positions() {
+  let trivia = skipper().whitespace();
+  let word = |s: SkipPosition| trivia.skip_at(s).lexeme(identifier(IdentifierStyle::Ascii));
+  assert_eq!(word(SkipPosition::Before).parse("  ab  "), Ok(("ab".to_string(), "  ")));
+  assert_eq!(word(SkipPosition::After).parse("ab  cd"), Ok(("ab".to_string(), "cd")));
+  assert!(word(SkipPosition::After).parse("  ab").is_err());
+  assert_eq!(word(SkipPosition::Both).parse(" ab "), Ok(("ab".to_string(), "")));
+
+  let pair = word(SkipPosition::Before).then(word(SkipPosition::Before));
+  assert_eq!(pair.parse(" a\n\tb"), Ok((("a".to_string(), "b".to_string()), "")));
+}
+
+#[test]
+fn test_case_insensitive() {
+  let ascii = str_exact("Straße", 1).ignore_ascii_case();
+  assert_eq!(ascii.parse("STRAßE!"), Ok((1, "!")));
+  assert!(ascii.parse("STRASSE").is_err());
+
+  let unicode = str_exact("Straße", 1).ignore_case();
+  assert_eq!(unicode.parse("STRASSE!"), Ok((1, "!")));
+  assert_eq!(unicode.parse("straße"), Ok((1, "")));
+  assert_eq!(str_exact("ΣΊΣΥΦΟΣ", ()).ignore_case().parse("σίσυφος."), Ok(((), ".")));
+  assert!(unicode.parse("STRAS").is_err());
+  assert!(unicode.parse("Strasze").is_err());
+
+  let select = keyword("select", "SELECT").ignore_ascii_case();
+  assert_eq!(select.parse("SELECT *"), Ok(("SELECT", " *")));
+  assert!(select.parse("Selection").is_err());
+  assert_eq!(keyword("größe", 2).ignore_case().parse("GRÖSSE ="), Ok((2, " =")));
+
+  assert_eq!(unicode.case(), CaseMatching::IgnoreUnicode);
+  assert_eq!(unicode.ignore_ascii_case().parse("STRASSE"), Err("Expected \"Straße\"".to_string()));
+  assert_eq!(keyword("größe", 2).ignore_case().with_style(IdentifierStyle::Unicode).parse("GRÖSSEé"), Err("Expected keyword `größe`".to_string()));
+}
+
+#[test]
+fn test_lexer() {
+  #[derive(Debug, PartialEq)]
+  enum Tok { If, Ident(String), Op(String) }
+
+  let lex = lexer()
+    .skip(skipper().whitespace().block_comment("/*", "*/"))
+    .regex("if", |_| Tok::If)
+    .regex("[a-z]+", |s| Tok::Ident(s.to_string()))
+    .regex("=|==", |s| Tok::Op(s.to_string()))
+    .regex("==", |s| Tok::Op(s.to_string()));
+
+  let tokens = lex.tokenize("if iffy\n  /* é */ == x").unwrap();
+  assert_eq!(tokens, vec![
+    (Tok::If, Span{start: 0, end: 2, line: 1, column: 1}),
+    (Tok::Ident("iffy".to_string()), Span{start: 3, end: 7, line: 1, column: 4}),
+    (Tok::Op("==".to_string()), Span{start: 19, end: 21, line: 2, column: 11}),
+    (Tok::Ident("x".to_string()), Span{start: 22, end: 23, line: 2, column: 14}),
+  ]);
+
+  assert_eq!(lex.tokenize("  "), Ok(vec![]));
+  assert_eq!(lex.tokenize("if\n\n  x 3"), Err("Unexpected character '3' at line 3, column 5".to_string()));
+  assert_eq!(lex.parse("x").map(|(t, rest)| (t.len(), rest)), Ok((1, "")));
+}
+
+#[test]
+fn test_lexer_skip_errors() {
+  let lex = lexer().skip(skipper().whitespace().block_comment("/*", "*/")).regex("[a-z]+", |s| s.to_string());
+  assert_eq!(lex.tokenize("ab /* never closed"), Err("Unterminated block comment at line 1, column 4".to_string()));
+  assert_eq!(lex.tokenize("ab /* */\n x /*"), Err("Unterminated block comment at line 2, column 4".to_string()));
+
+  let commas = lexer().skip_rule(take_while_char(|c| c == ',')).skip_rule(char_(';')).regex("[a-z]+", |s| s.to_string());
+  let tokens: Vec<_> = commas.tokenize(",a;,,b").unwrap().into_iter().map(|(t, _)| t).collect();
+  assert_eq!(tokens, vec!["a".to_string(), "b".to_string()]);
+}
+
+#[test]
+fn test_lexer_try_regex() {
+  let lex = lexer::<String>().skip(skipper().whitespace());
+  assert!(lex.try_regex("(", |s| s.to_string()).is_err());
+  let words = lex.try_regex("[a-z]+", |s| s.to_string()).unwrap();
+  assert_eq!(words.tokenize("ab cd").map(|t| t.len()), Ok(2));
+}
+
+#[test]
+fn test_lexer_builds_winning_token_only() {
+  use std::cell::Cell;
+  use std::rc::Rc;
+  let built = Rc::new(Cell::new(0));
+  let (b1, b2) = (built.clone(), built.clone());
+  let lex = lexer()
+    .regex("[a-z]", move |s| { b1.set(b1.get() + 1); s.to_string() })
+    .rule(identifier(IdentifierStyle::Ascii), move |s| { b2.set(b2.get() + 1); s });
+  assert_eq!(lex.tokenize("abc").unwrap().len(), 1);
+  assert_eq!(built.get(), 1);
+}
+
+#[test]
+fn test_regex_set() {
+  let ops = regex_set(&["=", "==", "=>", "[a-z]+"], |i, text: &str| (i, text.to_string()));
+  assert_eq!(ops.parse("==x"), Ok(((0, "=".to_string()), "=x")));
+  assert_eq!(ops.longest().parse("==x"), Ok(((1, "==".to_string()), "x")));
+  assert_eq!(ops.longest().parse("=>"), Ok(((2, "=>".to_string()), "")));
+  assert_eq!(ops.longest().parse("ab="), Ok(((3, "ab".to_string()), "=")));
+  assert_eq!(ops.parse(" ="), Err("None of the 4 patterns matched".to_string()));
+  assert!(try_regex_set(&["ok", "("], |i, _| i).is_err());
+
+  let lex = lexer()
+    .skip(skipper().whitespace())
+    .rule(regex_set(&["if", "[a-z]+"], |i, text| if i == 0 { None } else { Some(text.to_string()) }).longest(), |t| t);
+  let tokens: Vec<_> = lex.tokenize("if iffy").unwrap().into_iter().map(|(t, _)| t).collect();
+  assert_eq!(tokens, vec![None, Some("iffy".to_string())]);
+}
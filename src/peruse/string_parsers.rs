@@ -1,12 +1,20 @@
 
 
 use parsers::{Parser, ParserCombinator, ParseResult};
-use regex::{Captures, Regex};
+use regex::{Captures, Error, Regex, RegexSet};
+use regex_syntax::Expr;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::num::ParseIntError;
 use std::rc::Rc;
+use std::str::FromStr;
+use unicode_xid::UnicodeXID;
 
-pub type StringParser<T> = Parser<I=str, O=T>;
+pub type StringParser<T> = dyn Parser<I=str, O=T>;
     
-/// A string Parser that attempts to consume the given regex
+/// A string Parser that attempts to consume the given regex.  Only matches that start at the
+/// beginning of the input are accepted.
 #[derive(Clone)]
 pub struct RegexLiteralParser<T: Clone> {
   pub regex: Regex,
@@ -18,16 +26,111 @@ impl<T: Clone> Parser for RegexLiteralParser<T> {
   type O = T;
 
   fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, Self::O>{
-    self.regex.find(data).map(|(_, e)| (self.literal.clone(), &data[e..])).ok_or(format!("regex literal match fail"))
+    match self.regex.find(data) {
+      Some((0, e)) => Ok((self.literal.clone(), &data[e..])),
+      _ => Err("regex literal match fail".to_string())
+    }
   }
 }
 
 impl<T: Clone> ParserCombinator for RegexLiteralParser<T> {}
 
 
+/// How literal text is compared against the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseMatching {
+  /// The text must match exactly
+  Sensitive,
+  /// ASCII letters match regardless of case, everything else must match exactly
+  IgnoreAscii,
+  /// Text matches if it is equal after Unicode case folding, so `ß` matches `SS`
+  IgnoreUnicode,
+}
+
+/// Fold a character for caseless comparison
+fn case_fold(c: char) -> impl Iterator<Item=char> {
+  c.to_uppercase().flat_map(char::to_lowercase)
+}
+
+/// Case fold a whole string, done once when a parser is switched to `CaseMatching::IgnoreUnicode`
+fn fold_text(text: &str) -> Vec<char> {
+  text.chars().flat_map(case_fold).collect()
+}
+
+/// Check whether `data` starts with `text`, returning the number of bytes of `data` that matched.
+/// `target` is the case folded `text`, only used for `CaseMatching::IgnoreUnicode`.
+fn match_prefix(data: &str, text: &str, target: &[char], case: CaseMatching) -> Option<usize> {
+  match case {
+    CaseMatching::Sensitive => if data.starts_with(text) { Some(text.len()) } else { None },
+    CaseMatching::IgnoreAscii => data.get(..text.len()).filter(|prefix| prefix.eq_ignore_ascii_case(text)).map(str::len),
+    CaseMatching::IgnoreUnicode => {
+      if target.is_empty() {
+        return Some(0)
+      }
+      let mut matched = 0;
+      for (i, c) in data.char_indices() {
+        for folded in case_fold(c) {
+          if target.get(matched) != Some(&folded) {
+            return None
+          }
+          matched += 1;
+        }
+        if matched == target.len() {
+          return Some(i + c.len_utf8())
+        }
+      }
+      None
+    }
+  }
+}
+
+/// A string Parser that attempts to consume an exact piece of text
+#[derive(Clone)]
+pub struct ExactLiteralParser<T: Clone> {
+  pub text: String,
+  literal: T,
+  case: CaseMatching,
+  folded: Vec<char>,
+}
+
+impl<T: Clone> ExactLiteralParser<T> {
+
+  /// Match the text regardless of the case of ASCII letters
+  pub fn ignore_ascii_case(&self) -> Self {
+    ExactLiteralParser{case: CaseMatching::IgnoreAscii, folded: Vec::new(), ..self.clone()}
+  }
+
+  /// Match the text regardless of case, using Unicode case folding
+  pub fn ignore_case(&self) -> Self {
+    ExactLiteralParser{case: CaseMatching::IgnoreUnicode, folded: fold_text(&self.text), ..self.clone()}
+  }
+
+  /// How the text is compared against the input
+  pub fn case(&self) -> CaseMatching {
+    self.case
+  }
+}
+
+impl<T: Clone> Parser for ExactLiteralParser<T> {
+  type I = str;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, Self::O>{
+    match match_prefix(data, &self.text, &self.folded, self.case) {
+      Some(len) => Ok((self.literal.clone(), &data[len..])),
+      None => Err(format!("Expected {:?}", self.text))
+    }
+  }
+}
+
+impl<T: Clone> ParserCombinator for ExactLiteralParser<T> {}
+
+
+/// A string Parser that matches a regex and passes its captures to a closure.  Only matches that
+/// start at the beginning of the input are accepted.
 pub struct RegexCapturesParser<T, F: Fn(Captures) -> T> {
   pub regex: Regex,
-  f: Rc<Box<F>>
+  f: Rc<F>
 }
 
 impl<T, F: Fn(Captures) -> T> Parser for RegexCapturesParser<T, F> {
@@ -38,10 +141,10 @@ impl<T, F: Fn(Captures) -> T> Parser for RegexCapturesParser<T, F> {
   fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, T> {
     match self.regex.captures(data) {
       Some(caps) => match caps.pos(0) {
-        Some((_, e)) => Ok(((self.f)(caps), &data[e..])),
-        None => Err(format!("No Match"))
+        Some((0, e)) => Ok(((self.f)(caps), &data[e..])),
+        _ => Err("No Match".to_string())
       },
-      None => Err(format!("No Match"))
+      None => Err("No Match".to_string())
     }
   }
 }
@@ -55,19 +158,1500 @@ impl<T: Clone, F: Fn(Captures) -> T> Clone for RegexCapturesParser<T, F> {
   }
 }
 
+/// Which pattern a `RegexSetParser` picks when several of them match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetMatchMode {
+  /// The earliest pattern in the list that matches
+  First,
+  /// The pattern with the longest match, ties going to the earliest pattern
+  Longest,
+}
+
+/// A string Parser that matches many regexes at once, created with `regex_set`
+pub struct RegexSetParser<T, F: Fn(usize, &str) -> T> {
+  set: Rc<RegexSet>,
+  regexes: Rc<Vec<Regex>>,
+  pub mode: SetMatchMode,
+  f: Rc<F>,
+}
+
+impl<T, F: Fn(usize, &str) -> T> RegexSetParser<T, F> {
+
+  /// Pick the pattern with the longest match instead of the first one that matches
+  pub fn longest(&self) -> Self {
+    RegexSetParser{mode: SetMatchMode::Longest, ..self.clone()}
+  }
+}
+
+impl<T, F: Fn(usize, &str) -> T> Parser for RegexSetParser<T, F> {
+  type I = str;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, T> {
+    let mut best: Option<(usize, usize)> = None;
+    for index in self.set.matches(data).iter() {
+      let end = match self.regexes[index].find(data) {
+        Some((0, e)) => e,
+        _ => continue
+      };
+      if best.is_none_or(|(_, e)| end > e) {
+        best = Some((index, end));
+      }
+      if self.mode == SetMatchMode::First {
+        break
+      }
+    }
+    match best {
+      Some((index, end)) => Ok(((self.f)(index, &data[..end]), &data[end..])),
+      None => Err(format!("None of the {} patterns matched", self.regexes.len()))
+    }
+  }
+}
+
+impl<T, F: Fn(usize, &str) -> T> ParserCombinator for RegexSetParser<T, F> {}
+
+impl<T, F: Fn(usize, &str) -> T> Clone for RegexSetParser<T, F> {
+
+  fn clone(&self) -> Self {
+    RegexSetParser{set: self.set.clone(), regexes: self.regexes.clone(), mode: self.mode, f: self.f.clone()}
+  }
+}
+
+
+/// Wrap a regex pattern so it can only match at the start of the input.  The pattern is parsed and
+/// printed back out rather than spliced in as text, so flags set inside it and trailing `(?x)`
+/// comments can't leak into the anchoring group.
+fn anchored(pattern: &str) -> Result<String, Error> {
+  let expr = Expr::parse(pattern).map_err(Error::Syntax)?;
+  Ok(format!("^(?:{})", expr))
+}
 
+/// Create a parser that returns the given value when the input starts with a match of the regex.
+/// The regex is used as-is, including any options set with `RegexBuilder`, and matches that don't
+/// start at the beginning of the input are rejected.  Starting the regex with `^` avoids searching
+/// the rest of the input when there is no match.
 pub fn rlit<T: Clone>(r: Regex, l: T) -> RegexLiteralParser<T> {
   RegexLiteralParser{regex: r, literal: l}
 }
+
+/// Create a parser that returns the given value when the input starts with a match of the regex
+/// `s`.  Note that `s` is a regex, not plain text, so metacharacters such as `(` or `+` must be
+/// escaped.  Use `str_exact` to match text literally.  Prefix the regex with `(?i)` to match
+/// case-insensitively.
+///
+/// # Panics
+/// Panics if `s` is not a valid regex, see `try_str_lit` for a non-panicking version.
 pub fn str_lit<T: Clone>(s: &str, l: T) -> RegexLiteralParser<T> {
-  let r = format!("^{}", s);
-  let regex = Regex::new(&r).unwrap();
-  RegexLiteralParser{regex: regex, literal: l}
+  try_str_lit(s, l).unwrap()
 }
 
+/// Like `str_lit`, but returns the regex compile error instead of panicking if `s` is not a valid
+/// regex.  Use this when the pattern comes from user input.
+///
+/// # Examples
+/// ```
+/// # use peruse::string_parsers::*;
+/// assert!(try_str_lit("[a-z]+", ()).is_ok());
+/// assert!(try_str_lit("(", ()).is_err());
+/// ```
+pub fn try_str_lit<T: Clone>(s: &str, l: T) -> Result<RegexLiteralParser<T>, Error> {
+  let regex = Regex::new(&anchored(s)?)?;
+  Ok(RegexLiteralParser{regex, literal: l})
+}
+
+/// Create a parser that matches the regex `reg` at the start of the input and builds its output
+/// from the captures with the closure `f`.  Capture group numbering is unaffected by the anchoring.
+///
+/// # Panics
+/// Panics if `reg` is not a valid regex, see `try_capture` for a non-panicking version.
 pub fn capture<T, F: 'static + Fn(Captures) -> T>(reg: &str, f: F) -> RegexCapturesParser<T, F> {
-  let regex = Regex::new(reg).unwrap();
+  try_capture(reg, f).unwrap()
+}
+
+/// Like `capture`, but returns the regex compile error instead of panicking if `reg` is not a
+/// valid regex.  Use this when the pattern comes from user input.
+pub fn try_capture<T, F: 'static + Fn(Captures) -> T>(reg: &str, f: F) -> Result<RegexCapturesParser<T, F>, Error> {
+  let regex = Regex::new(&anchored(reg)?)?;
+
+  Ok(RegexCapturesParser{regex, f: Rc::new(f)})
+}
+
+/// Create a parser that tries all of the given regexes at the start of the input in a single pass,
+/// building its output from the index of the pattern that matched and the matched text with the
+/// closure `f`.  By default the earliest pattern in the list wins, use `longest` to pick the
+/// longest match instead.
+///
+/// This does the same job as `one_of` over a list of `capture` parsers, but compiles all of the
+/// patterns into one `RegexSet`, so the input is only scanned once no matter how many patterns
+/// there are.
+///
+/// # Panics
+/// Panics if any pattern is not a valid regex, see `try_regex_set` for a non-panicking version.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// #[derive(Debug, PartialEq)]
+/// enum Token { Let, Ident(String), Num(String) }
+///
+/// let token = regex_set(&["let", "[a-z]+", "[0-9]+"], |i, text| match i {
+///   0 => Token::Let,
+///   1 => Token::Ident(text.to_string()),
+///   _ => Token::Num(text.to_string()),
+/// });
+/// assert_eq!(token.parse("letter"), Ok((Token::Let, "ter")));
+/// assert_eq!(token.longest().parse("letter"), Ok((Token::Ident("letter".to_string()), "")));
+/// assert_eq!(token.parse("42!"), Ok((Token::Num("42".to_string()), "!")));
+/// assert!(token.parse("!").is_err());
+/// ```
+pub fn regex_set<T, F: 'static + Fn(usize, &str) -> T>(patterns: &[&str], f: F) -> RegexSetParser<T, F> {
+  try_regex_set(patterns, f).unwrap()
+}
+
+/// Like `regex_set`, but returns the regex compile error instead of panicking if a pattern is not
+/// a valid regex
+pub fn try_regex_set<T, F: 'static + Fn(usize, &str) -> T>(patterns: &[&str], f: F) -> Result<RegexSetParser<T, F>, Error> {
+  let anchored = patterns.iter().map(|p| anchored(p)).collect::<Result<Vec<_>, _>>()?;
+  let set = RegexSet::new(&anchored)?;
+  let regexes = anchored.iter().map(|p| Regex::new(p)).collect::<Result<Vec<_>, _>>()?;
+  Ok(RegexSetParser{set: Rc::new(set), regexes: Rc::new(regexes), mode: SetMatchMode::First, f: Rc::new(f)})
+}
+
+/// Create a parser that matches a regex with named capture groups at the start of the input and
+/// converts the groups into a value using its `FromCaptures` implementation.  A
+/// `HashMap<String, String>` of all matched groups can be produced out of the box, and structs can
+/// derive `FromCaptures` (with the `derive` feature) to have each field parsed from the group of
+/// the same name.
+///
+/// # Panics
+/// Panics if `reg` is not a valid regex, see `try_named_capture` for a non-panicking version.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// # use std::collections::HashMap;
+/// let parser = named_capture::<HashMap<String, String>>(r"(?P<key>\w+)=(?P<value>\w+)");
+/// let (map, rest) = parser.parse("size=10;").unwrap();
+/// assert_eq!(map["key"], "size");
+/// assert_eq!(map["value"], "10");
+/// assert_eq!(rest, ";");
+/// ```
+pub fn named_capture<T: FromCaptures>(reg: &str) -> NamedCaptureParser<T> {
+  try_named_capture(reg).unwrap()
+}
+
+/// Like `named_capture`, but returns the regex compile error instead of panicking if `reg` is not a
+/// valid regex
+pub fn try_named_capture<T: FromCaptures>(reg: &str) -> Result<NamedCaptureParser<T>, Error> {
+  let regex = Regex::new(&anchored(reg)?)?;
+  let indices = regex.capture_names().enumerate().filter_map(|(i, name)| name.map(|n| (n.to_string(), i))).collect();
+  Ok(NamedCaptureParser{regex, indices: Rc::new(indices), _marker: PhantomData})
+}
+
+/// Create a parser that returns the given value when the input starts with exactly the text `s`.
+/// Unlike `str_lit`, no regex is involved, so any text can be matched as-is.  Use `ignore_case` or
+/// `ignore_ascii_case` to match regardless of case; the given value is returned either way.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let parser = str_exact("(", 1).or(str_exact("+", 2)).repeat();
+/// assert_eq!(parser.parse("(+(x"), Ok((vec![1, 2, 1], "x")));
+///
+/// let select = str_exact("select", "SELECT").ignore_ascii_case();
+/// assert_eq!(select.parse("SeLeCt *"), Ok(("SELECT", " *")));
+/// ```
+pub fn str_exact<T: Clone>(s: &str, l: T) -> ExactLiteralParser<T> {
+  ExactLiteralParser{text: s.to_string(), literal: l, case: CaseMatching::Sensitive, folded: Vec::new()}
+}
+
+/// Create a parser that consumes a single character if it satisfies the given predicate
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let vowel = satisfy(|c| "aeiou".contains(c));
+/// assert_eq!(vowel.parse("abc"), Ok(('a', "bc")));
+/// assert!(vowel.parse("bc").is_err());
+/// ```
+pub fn satisfy<F: 'static + Fn(char) -> bool>(f: F) -> CharParser {
+  CharParser{predicate: Rc::new(f), expected: "a matching character".to_string()}
+}
+
+/// Create a parser that consumes exactly the character `c`
+pub fn char_(c: char) -> CharParser {
+  CharParser{predicate: Rc::new(move |d| d == c), expected: format!("{:?}", c)}
+}
+
+/// Create a parser that consumes a single ASCII digit
+pub fn digit() -> CharParser {
+  CharParser{predicate: Rc::new(|c: char| c.is_ascii_digit()), expected: "a digit".to_string()}
+}
+
+/// Create a parser that consumes a single alphabetic character
+pub fn alpha() -> CharParser {
+  CharParser{predicate: Rc::new(char::is_alphabetic), expected: "a letter".to_string()}
+}
+
+/// Create a parser that consumes a single alphabetic or numeric character
+pub fn alphanumeric() -> CharParser {
+  CharParser{predicate: Rc::new(char::is_alphanumeric), expected: "a letter or digit".to_string()}
+}
+
+/// Create a parser that consumes a single whitespace character
+pub fn whitespace() -> CharParser {
+  CharParser{predicate: Rc::new(char::is_whitespace), expected: "whitespace".to_string()}
+}
+
+/// Create a parser that consumes a single character if it appears in `chars`
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let op = one_of_chars("+-*/");
+/// assert_eq!(op.parse("*2"), Ok(('*', "2")));
+/// assert_eq!(op.parse("2"), Err("Expected one of \"+-*/\"".to_string()));
+/// ```
+pub fn one_of_chars(chars: &str) -> CharParser {
+  let set = chars.to_string();
+  CharParser{predicate: Rc::new(move |c| set.contains(c)), expected: format!("one of {:?}", chars)}
+}
+
+/// Create a parser that consumes a single character if it does *not* appear in `chars`
+pub fn none_of_chars(chars: &str) -> CharParser {
+  let set = chars.to_string();
+  CharParser{predicate: Rc::new(move |c| !set.contains(c)), expected: format!("none of {:?}", chars)}
+}
+
+/// Create a parser that consumes characters for as long as they satisfy the given predicate and
+/// returns them.  This never fails; if the first character doesn't match an empty string is
+/// returned.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let number = take_while_char(|c| c.is_ascii_digit());
+/// assert_eq!(number.parse("123abc"), Ok(("123".to_string(), "abc")));
+/// ```
+pub fn take_while_char<F: 'static + Fn(char) -> bool>(f: F) -> TakeWhileCharParser<F> {
+  TakeWhileCharParser{predicate: Rc::new(f)}
+}
+
+/// Create a parser for a decimal integer of type `T`.  Signed types accept a leading `-`.  Values
+/// that don't fit in `T` are reported as an error rather than wrapping.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// assert_eq!(integer::<i32>().parse("-42;"), Ok((-42, ";")));
+/// assert!(integer::<u8>().parse("256").is_err());
+/// assert_eq!(integer::<u32>().with_separators().parse("1_000_000"), Ok((1000000, "")));
+/// ```
+pub fn integer<T: Integer>() -> IntegerParser<T> {
+  IntegerParser{radix: IntegerRadix::Decimal, separators: false, _marker: PhantomData}
+}
+
+/// Create a parser for a hexadecimal integer with a `0x` prefix, such as `0xFF`
+pub fn hex_integer<T: Integer>() -> IntegerParser<T> {
+  IntegerParser{radix: IntegerRadix::Hexadecimal, separators: false, _marker: PhantomData}
+}
+
+/// Create a parser for an octal integer with a `0o` prefix, such as `0o755`
+pub fn octal_integer<T: Integer>() -> IntegerParser<T> {
+  IntegerParser{radix: IntegerRadix::Octal, separators: false, _marker: PhantomData}
+}
+
+/// Create a parser for a binary integer with a `0b` prefix, such as `0b1010`
+pub fn binary_integer<T: Integer>() -> IntegerParser<T> {
+  IntegerParser{radix: IntegerRadix::Binary, separators: false, _marker: PhantomData}
+}
+
+/// Create a parser for an integer literal in any base.  The base is picked from the prefix (`0x`,
+/// `0o` or `0b`) and defaults to decimal.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let parser = int_literal::<i64>();
+/// assert_eq!(parser.parse("0x1F"), Ok((31, "")));
+/// assert_eq!(parser.parse("-0b101"), Ok((-5, "")));
+/// assert_eq!(parser.parse("017"), Ok((17, "")));
+/// ```
+pub fn int_literal<T: Integer>() -> IntegerParser<T> {
+  IntegerParser{radix: IntegerRadix::Prefixed, separators: false, _marker: PhantomData}
+}
+
+/// Create a parser for a decimal floating point number of type `T`, with an optional sign,
+/// fractional part and exponent, such as `-1.5e-3`.  The integer part is required.  Values too
+/// large to be represented are reported as an error.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// assert_eq!(float::<f64>().parse("2.5e2 "), Ok((250.0, " ")));
+/// assert_eq!(float::<f32>().parse("3.method"), Ok((3.0, ".method")));
+/// assert!(float::<f32>().parse("1e39").is_err());
+/// ```
+pub fn float<T: Float>() -> FloatParser<T> {
+  FloatParser{separators: false, _marker: PhantomData}
+}
+
+/// Create a parser for a quoted string literal that decodes escape sequences according to the
+/// given dialect and returns the decoded contents.  By default only double quotes are accepted,
+/// use `with_quotes` to change that.  Invalid escapes and disallowed characters are reported with
+/// their byte offset from the start of the literal, and unterminated strings with the offset where
+/// the input ran out.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let json = quoted_string(EscapeDialect::Json);
+/// assert_eq!(json.parse(r#""a\n\u00e9\ud83d\ude00" x"#), Ok(("a\né😀".to_string(), " x")));
+/// assert_eq!(json.parse(r#""\q""#), Err("Invalid escape sequence `\\q` at offset 1".to_string()));
+///
+/// let rust = quoted_string(EscapeDialect::Rust).with_quotes("\"'");
+/// assert_eq!(rust.parse(r"'\u{1F600}\x41'"), Ok(("😀A".to_string(), "")));
+/// ```
+pub fn quoted_string(dialect: EscapeDialect) -> QuotedStringParser {
+  QuotedStringParser{dialect, quotes: "\"".to_string()}
+}
+
+/// Create a parser for identifiers of the given style, returning the identifier.  Use
+/// `with_reserved` to reject keywords.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let ident = identifier(IdentifierStyle::Ascii).with_reserved(&["if", "while"]);
+/// assert_eq!(ident.parse("iffy = 1"), Ok(("iffy".to_string(), " = 1")));
+/// assert_eq!(ident.parse("if x"), Err("`if` is a reserved word".to_string()));
+/// assert!(ident.parse("1abc").is_err());
+/// ```
+pub fn identifier(style: IdentifierStyle) -> IdentifierParser {
+  IdentifierParser{style, reserved: Rc::new(HashSet::new())}
+}
+
+/// Create a parser that returns the given value when the input starts with the keyword `kw`, as
+/// long as it isn't immediately followed by another identifier character.  By default the
+/// boundary is checked with `IdentifierStyle::Ascii`, see `with_style` to change that.  Like
+/// `str_exact`, keywords can be made case-insensitive with `ignore_case` or `ignore_ascii_case`.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let kw = keyword("if", 1);
+/// assert_eq!(kw.parse("if(x)"), Ok((1, "(x)")));
+/// assert!(kw.parse("iffy").is_err());
+/// assert!(kw.parse("if_x").is_err());
+/// ```
+pub fn keyword<T: Clone>(kw: &str, l: T) -> KeywordParser<T> {
+  KeywordParser{keyword: kw.to_string(), literal: l, style: IdentifierStyle::Ascii, case: CaseMatching::Sensitive, folded: Vec::new()}
+}
+
+/// Create an empty `Skipper`.  Use its builder methods to describe what counts as trivia, then
+/// wrap token parsers with `lexeme` so they skip the trivia automatically.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::string_parsers::*;
+/// let trivia = skipper().whitespace().line_comment("//").nested_block_comment("/*", "*/");
+/// let num = trivia.lexeme(integer::<i32>());
+/// let plus = trivia.lexeme(str_exact("+", ()));
+/// let sum = repsep(num, plus).map(|v| v.iter().sum::<i32>()).then_l(trivia.clone());
+///
+/// let input = "1 + /* two /* nested */ */ 2 // trailing\n + 3 ";
+/// assert_eq!(sum.parse(input), Ok((6, "")));
+/// ```
+pub fn skipper() -> Skipper {
+  Skipper{whitespace: false, line_comments: Vec::new(), block_comments: Vec::new(), position: SkipPosition::Before}
+}
+
+/// Create an empty `Lexer`.  Add token rules with `regex` and `rule`, and trivia to drop between
+/// tokens with `skip` or `skip_rule`, then call `tokenize` to turn a string into tokens tagged with
+/// their `Span`.
+///
+/// At each position the rule with the longest match wins, with ties going to the rule that was
+/// added first, so keywords should be added before a general identifier rule.
+///
+/// # Examples
+/// ```
+/// # use peruse::parsers::*;
+/// # use peruse::slice_parsers::*;
+/// # use peruse::string_parsers::*;
+/// #[derive(Clone, Debug, PartialEq, Eq)]
+/// enum Token { Let, Ident(String), Num(i64), Equals }
+///
+/// let lex = lexer()
+///   .skip(skipper().whitespace().line_comment("#"))
+///   .rule(keyword("let", ()), |_| Token::Let)
+///   .regex(r"[a-z]+", |s| Token::Ident(s.to_string()))
+///   .rule(integer::<i64>(), Token::Num)
+///   .regex("=", |_| Token::Equals);
+///
+/// let (tokens, spans): (Vec<Token>, Vec<Span>) = lex.tokenize("let x = 5 # five").unwrap().into_iter().unzip();
+/// assert_eq!(tokens, vec![Token::Let, Token::Ident("x".to_string()), Token::Equals, Token::Num(5)]);
+/// assert_eq!(spans[3], Span{start: 8, end: 9, line: 1, column: 9});
+///
+/// // positions in the token slice map back to the source through the spans
+/// let (_, rest) = lit(Token::Let).parse(&tokens).unwrap();
+/// let next = spans[tokens.len() - rest.len()];
+/// assert_eq!((next.line, next.column), (1, 5));
+///
+/// assert_eq!(lex.tokenize("let x\n= ?"), Err("Unexpected character '?' at line 2, column 3".to_string()));
+/// ```
+///
+/// Errors from a `Skipper` are reported at the position of the offending comment.
+/// ```
+/// # use peruse::string_parsers::*;
+/// let lex = lexer().skip(skipper().whitespace().block_comment("/*", "*/")).regex("[a-z]+", |s| s.to_string());
+/// assert_eq!(lex.tokenize("ab\n  /* never closed"), Err("Unterminated block comment at line 2, column 3".to_string()));
+/// ```
+pub fn lexer<T>() -> Lexer<T> {
+  Lexer{rules: Vec::new(), skips: Vec::new()}
+}
+
+/// Create a parser that consumes text until the `end` parser matches, returning the consumed text.
+/// The text matched by `end` is not consumed.  If `end` never matches, the parser fails.
+pub fn str_take_until<P: Parser<I=str>>(end: P) -> StrTakeUntilParser<P> {
+  StrTakeUntilParser{end}
+}
+
+
+/// A string Parser that consumes a single character satisfying a predicate
+#[derive(Clone)]
+pub struct CharParser {
+  predicate: Rc<dyn Fn(char) -> bool>,
+  expected: String,
+}
+
+impl Parser for CharParser {
+  type I = str;
+  type O = char;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, char> {
+    match data.chars().next() {
+      Some(c) if (self.predicate)(c) => Ok((c, &data[c.len_utf8()..])),
+      Some(_) => Err(format!("Expected {}", self.expected)),
+      None    => Err("ran out of data".to_string())
+    }
+  }
+}
+
+impl ParserCombinator for CharParser {}
+
+
+/// A string Parser that consumes characters as long as they satisfy a predicate
+pub struct TakeWhileCharParser<F: Fn(char) -> bool> {
+  predicate: Rc<F>,
+}
+
+impl<F: Fn(char) -> bool> Parser for TakeWhileCharParser<F> {
+  type I = str;
+  type O = String;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, String> {
+    let end = data.char_indices().find(|&(_, c)| !(self.predicate)(c)).map_or(data.len(), |(i, _)| i);
+    Ok((data[..end].to_string(), &data[end..]))
+  }
+}
+
+impl<F: Fn(char) -> bool> ParserCombinator for TakeWhileCharParser<F> {}
+
+impl<F: Fn(char) -> bool> Clone for TakeWhileCharParser<F> {
+  fn clone(&self) -> Self {
+    TakeWhileCharParser{predicate: self.predicate.clone()}
+  }
+}
+
+
+/// Integer types that can be produced by an `IntegerParser`
+pub trait Integer: Sized {
+  /// Whether the type accepts a leading `-`
+  const SIGNED: bool;
+
+  fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError>;
+}
+
+macro_rules! integer_impls {
+  ($signed:expr, $($t:ty),+) => {
+    $(
+      impl Integer for $t {
+        const SIGNED: bool = $signed;
+
+        fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+          <$t>::from_str_radix(s, radix)
+        }
+      }
+    )+
+  }
+}
+
+integer_impls!(true, i8, i16, i32, i64, i128, isize);
+integer_impls!(false, u8, u16, u32, u64, u128, usize);
+
+/// Floating point types that can be produced by a `FloatParser`
+pub trait Float: FromStr {
+  fn is_infinite(&self) -> bool;
+}
+
+impl Float for f32 {
+  fn is_infinite(&self) -> bool {
+    f32::is_infinite(*self)
+  }
+}
+
+impl Float for f64 {
+  fn is_infinite(&self) -> bool {
+    f64::is_infinite(*self)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IntegerRadix {
+  Decimal,
+  Hexadecimal,
+  Octal,
+  Binary,
+  Prefixed,
+}
+
+/// Consume digits of the given radix from the start of `data`, optionally allowing `_` separators
+/// after the first digit.  Returns the digits without separators and the number of bytes consumed.
+fn scan_digits(data: &str, radix: u32, separators: bool) -> (String, usize) {
+  let mut digits = String::new();
+  let mut end = 0;
+  for (i, c) in data.char_indices() {
+    if c.is_digit(radix) {
+      digits.push(c);
+    } else if c == '_' && separators && !digits.is_empty() {
+      //separators are skipped
+    } else {
+      break;
+    }
+    end = i + 1;
+  }
+  (digits, end)
+}
+
+/// Strip a radix prefix (such as `0x`) from the start of `data`
+fn strip_radix_prefix(data: &str, prefix: char) -> Option<&str> {
+  let mut chars = data.chars();
+  match (chars.next(), chars.next()) {
+    (Some('0'), Some(p)) if p.to_ascii_lowercase() == prefix => Some(&data[2..]),
+    _ => None
+  }
+}
+
+/// A string Parser for integer literals, see `integer` and `int_literal`
+pub struct IntegerParser<T: Integer> {
+  radix: IntegerRadix,
+  pub separators: bool,
+  _marker: PhantomData<T>
+}
+
+impl<T: Integer> IntegerParser<T> {
+
+  /// Allow `_` digit separators, as in `1_000_000`
+  pub fn with_separators(&self) -> Self {
+    IntegerParser{radix: self.radix, separators: true, _marker: PhantomData}
+  }
+}
+
+impl<T: Integer> Parser for IntegerParser<T> {
+  type I = str;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, T> {
+    let mut text = String::new();
+    let mut rest = data;
+    if T::SIGNED && rest.starts_with('-') {
+      text.push('-');
+      rest = &rest[1..];
+    }
+    let prefixes = [(IntegerRadix::Hexadecimal, 'x', 16), (IntegerRadix::Octal, 'o', 8), (IntegerRadix::Binary, 'b', 2)];
+    let mut radix = 10;
+    for &(kind, prefix, r) in prefixes.iter() {
+      if self.radix == kind || self.radix == IntegerRadix::Prefixed {
+        match strip_radix_prefix(rest, prefix) {
+          Some(digits) => {
+            radix = r;
+            rest = digits;
+            break;
+          }
+          None if self.radix == kind => return Err(format!("Expected an integer with a 0{} prefix", prefix)),
+          None => {}
+        }
+      }
+    }
+    let (digits, n) = scan_digits(rest, radix, self.separators);
+    if digits.is_empty() {
+      return Err("Expected an integer".to_string())
+    }
+    text.push_str(&digits);
+    let end = data.len() - rest.len() + n;
+    match T::from_str_radix(&text, radix) {
+      Ok(value) => Ok((value, &data[end..])),
+      Err(err) => Err(format!("Invalid integer literal {:?}: {}", &data[..end], err))
+    }
+  }
+}
+
+impl<T: Integer> ParserCombinator for IntegerParser<T> {}
+
+impl<T: Integer> Clone for IntegerParser<T> {
+  fn clone(&self) -> Self {
+    IntegerParser{radix: self.radix, separators: self.separators, _marker: PhantomData}
+  }
+}
+
+
+/// A string Parser for decimal floating point literals, see `float`
+pub struct FloatParser<T: Float> {
+  pub separators: bool,
+  _marker: PhantomData<T>
+}
+
+impl<T: Float> FloatParser<T> {
+
+  /// Allow `_` digit separators, as in `1_000.5`
+  pub fn with_separators(&self) -> Self {
+    FloatParser{separators: true, _marker: PhantomData}
+  }
+}
+
+impl<T: Float> Parser for FloatParser<T> {
+  type I = str;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, T> {
+    let mut text = String::new();
+    let mut end = 0;
+    if data.starts_with('-') || data.starts_with('+') {
+      text.push_str(&data[..1]);
+      end = 1;
+    }
+    let (whole, n) = scan_digits(&data[end..], 10, self.separators);
+    if whole.is_empty() {
+      return Err("Expected a number".to_string())
+    }
+    text.push_str(&whole);
+    end += n;
+
+    //the fraction and exponent are only consumed if digits follow them
+    if data[end..].starts_with('.') {
+      let (fraction, n) = scan_digits(&data[end + 1..], 10, self.separators);
+      if !fraction.is_empty() {
+        text.push('.');
+        text.push_str(&fraction);
+        end += 1 + n;
+      }
+    }
+    if data[end..].starts_with('e') || data[end..].starts_with('E') {
+      let mut exp_start = end + 1;
+      let mut exp_text = "e".to_string();
+      if data[exp_start..].starts_with('-') || data[exp_start..].starts_with('+') {
+        exp_text.push_str(&data[exp_start..exp_start + 1]);
+        exp_start += 1;
+      }
+      let (exponent, n) = scan_digits(&data[exp_start..], 10, self.separators);
+      if !exponent.is_empty() {
+        text.push_str(&exp_text);
+        text.push_str(&exponent);
+        end = exp_start + n;
+      }
+    }
+
+    match T::from_str(&text) {
+      Ok(ref value) if value.is_infinite() => Err(format!("Float literal {:?} is out of range", &data[..end])),
+      Ok(value) => Ok((value, &data[end..])),
+      Err(_) => Err(format!("Invalid float literal {:?}", &data[..end]))
+    }
+  }
+}
+
+impl<T: Float> ParserCombinator for FloatParser<T> {}
+
+impl<T: Float> Clone for FloatParser<T> {
+  fn clone(&self) -> Self {
+    FloatParser{separators: self.separators, _marker: PhantomData}
+  }
+}
+
+
+/// The escape sequences understood by a `QuotedStringParser`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscapeDialect {
+  /// `\" \\ \/ \b \f \n \r \t` and `\uXXXX` (with surrogate pairs).  Control characters must
+  /// be escaped.
+  Json,
+  /// `\n \r \t \\ \0 \' \"`, `\xHH` (up to `\x7F`), `\u{H...}` and `\` followed by a newline,
+  /// which skips the newline and any leading whitespace on the next line.
+  Rust,
+  /// `\a \b \f \n \r \t \v \\ \' \" \?`, octal `\ooo`, `\xH...`, `\uXXXX` and `\UXXXXXXXX`.
+  /// Newlines must be escaped.
+  C,
+}
+
+/// Parse a string made up entirely of hex digits
+fn hex_value(digits: &str) -> Option<u32> {
+  if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+    return None
+  }
+  u32::from_str_radix(digits, 16).ok()
+}
+
+/// A string Parser for quoted string literals, see `quoted_string`
+#[derive(Clone)]
+pub struct QuotedStringParser {
+  pub dialect: EscapeDialect,
+  pub quotes: String,
+}
+
+impl QuotedStringParser {
+
+  /// Accept any of the given characters as the opening quote.  The literal must be closed with the
+  /// same character it was opened with.
+  pub fn with_quotes(&self, quotes: &str) -> Self {
+    QuotedStringParser{dialect: self.dialect, quotes: quotes.to_string()}
+  }
+
+  /// Decode the escape sequence whose backslash is at byte `start` of `data`, returning the
+  /// decoded character (if any) and the position just past the sequence
+  fn unescape(&self, data: &str, start: usize) -> Result<(Option<char>, usize), String> {
+    let rest = &data[start + 1..];
+    let c = match rest.chars().next() {
+      Some(c) => c,
+      None => return Err(format!("Unterminated string literal at offset {}", data.len()))
+    };
+    let after = start + 1 + c.len_utf8();
+    let invalid = |mut end: usize| {
+      while !data.is_char_boundary(end) {
+        end -= 1;
+      }
+      format!("Invalid escape sequence `{}` at offset {}", &data[start..end], start)
+    };
+
+    let simple = match (self.dialect, c) {
+      (_, 'n') => Some('\n'),
+      (_, 'r') => Some('\r'),
+      (_, 't') => Some('\t'),
+      (_, '\\') => Some('\\'),
+      (_, '"') => Some('"'),
+      (EscapeDialect::Json, '/') => Some('/'),
+      (EscapeDialect::Json, 'b') | (EscapeDialect::C, 'b') => Some('\u{8}'),
+      (EscapeDialect::Json, 'f') | (EscapeDialect::C, 'f') => Some('\u{c}'),
+      (EscapeDialect::Rust, '0') => Some('\0'),
+      (EscapeDialect::Rust, '\'') | (EscapeDialect::C, '\'') => Some('\''),
+      (EscapeDialect::C, 'a') => Some('\u{7}'),
+      (EscapeDialect::C, 'v') => Some('\u{b}'),
+      (EscapeDialect::C, '?') => Some('?'),
+      _ => None
+    };
+    if let Some(decoded) = simple {
+      return Ok((Some(decoded), after))
+    }
+
+    match (self.dialect, c) {
+      (EscapeDialect::Json, 'u') => {
+        let end = (after + 4).min(data.len());
+        let high = data.get(after..after + 4).and_then(hex_value).ok_or_else(|| invalid(end))?;
+        if (0xDC00..0xE000).contains(&high) {
+          return Err(format!("Unpaired surrogate `{}` at offset {}", &data[start..after + 4], start))
+        }
+        if !(0xD800..0xDC00).contains(&high) {
+          return char::from_u32(high).map(|ch| (Some(ch), after + 4)).ok_or_else(|| invalid(after + 4))
+        }
+        let low = match data.get(after + 4..after + 10) {
+          Some(next) if next.starts_with("\\u") => hex_value(&next[2..]).filter(|low| (0xDC00..0xE000).contains(low)),
+          _ => None
+        };
+        match low {
+          Some(low) => {
+            let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            char::from_u32(code).map(|ch| (Some(ch), after + 10)).ok_or_else(|| invalid(after + 10))
+          },
+          None => Err(format!("Unpaired surrogate `{}` at offset {}", &data[start..after + 4], start))
+        }
+      },
+      (EscapeDialect::Rust, 'x') => {
+        let end = (after + 2).min(data.len());
+        match data.get(after..after + 2).and_then(hex_value) {
+          Some(v) if v <= 0x7F => Ok((char::from_u32(v), after + 2)),
+          _ => Err(invalid(end))
+        }
+      },
+      (EscapeDialect::Rust, 'u') => {
+        let close = if data[after..].starts_with('{') { data[after..].find('}') } else { None };
+        match close {
+          Some(close) if close <= 7 => {
+            let end = after + close + 1;
+            match hex_value(&data[after + 1..after + close]).and_then(char::from_u32) {
+              Some(ch) => Ok((Some(ch), end)),
+              None => Err(invalid(end))
+            }
+          },
+          _ => Err(invalid(after))
+        }
+      },
+      (EscapeDialect::Rust, '\n') => {
+        let skipped = data[after..].len() - data[after..].trim_start().len();
+        Ok((None, after + skipped))
+      },
+      (EscapeDialect::C, 'x') => {
+        let len = data[after..].chars().take_while(|c| c.is_ascii_hexdigit()).count();
+        match hex_value(&data[after..after + len]).and_then(char::from_u32) {
+          Some(ch) => Ok((Some(ch), after + len)),
+          None => Err(invalid(after + len))
+        }
+      },
+      (EscapeDialect::C, '0'..='7') => {
+        let len = data[start + 1..].chars().take(3).take_while(|c| c.is_digit(8)).count();
+        let value = u32::from_str_radix(&data[start + 1..start + 1 + len], 8).ok();
+        match value.and_then(char::from_u32) {
+          Some(ch) => Ok((Some(ch), start + 1 + len)),
+          None => Err(invalid(start + 1 + len))
+        }
+      },
+      (EscapeDialect::C, 'u') | (EscapeDialect::C, 'U') => {
+        let digits = if c == 'u' { 4 } else { 8 };
+        let end = (after + digits).min(data.len());
+        match data.get(after..after + digits).and_then(hex_value).and_then(char::from_u32) {
+          Some(ch) => Ok((Some(ch), after + digits)),
+          None => Err(invalid(end))
+        }
+      },
+      _ => Err(invalid(after))
+    }
+  }
+
+  /// Whether the character may appear unescaped inside a literal
+  fn allows_raw(&self, c: char) -> bool {
+    match self.dialect {
+      EscapeDialect::Json => c >= ' ',
+      EscapeDialect::Rust => true,
+      EscapeDialect::C => c != '\n',
+    }
+  }
+}
+
+impl Parser for QuotedStringParser {
+  type I = str;
+  type O = String;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, String> {
+    let quote = match data.chars().next() {
+      Some(c) if self.quotes.contains(c) => c,
+      _ => return Err(format!("Expected a string literal starting with one of {:?}", self.quotes))
+    };
+    let mut out = String::new();
+    let mut pos = quote.len_utf8();
+    while let Some(c) = data[pos..].chars().next() {
+      if c == quote {
+        return Ok((out, &data[pos + c.len_utf8()..]))
+      } else if c == '\\' {
+        let (decoded, next) = self.unescape(data, pos)?;
+        if let Some(decoded) = decoded {
+          out.push(decoded);
+        }
+        pos = next;
+      } else if self.allows_raw(c) {
+        out.push(c);
+        pos += c.len_utf8();
+      } else {
+        return Err(format!("Invalid character {:?} in string literal at offset {}", c, pos))
+      }
+    }
+    Err(format!("Unterminated string literal at offset {}", pos))
+  }
+}
+
+impl ParserCombinator for QuotedStringParser {}
+
+
+/// The set of characters identifiers are made of
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentifierStyle {
+  /// `[A-Za-z_][A-Za-z0-9_]*`
+  Ascii,
+  /// A character with the Unicode `XID_Start` property or `_`, followed by any number of
+  /// `XID_Continue` characters, as in Rust identifiers
+  Unicode,
+}
+
+impl IdentifierStyle {
+
+  /// Whether `c` can begin an identifier
+  pub fn is_start(&self, c: char) -> bool {
+    match *self {
+      IdentifierStyle::Ascii => c.is_ascii_alphabetic() || c == '_',
+      IdentifierStyle::Unicode => UnicodeXID::is_xid_start(c) || c == '_',
+    }
+  }
+
+  /// Whether `c` can appear after the first character of an identifier
+  pub fn is_continue(&self, c: char) -> bool {
+    match *self {
+      IdentifierStyle::Ascii => c.is_ascii_alphanumeric() || c == '_',
+      IdentifierStyle::Unicode => UnicodeXID::is_xid_continue(c),
+    }
+  }
+}
+
+/// A string Parser for identifiers, see `identifier`
+#[derive(Clone)]
+pub struct IdentifierParser {
+  pub style: IdentifierStyle,
+  reserved: Rc<HashSet<String>>,
+}
+
+impl IdentifierParser {
+
+  /// Reject the given words, even though they are otherwise valid identifiers
+  pub fn with_reserved(&self, words: &[&str]) -> Self {
+    let mut reserved = (*self.reserved).clone();
+    reserved.extend(words.iter().map(|w| w.to_string()));
+    IdentifierParser{style: self.style, reserved: Rc::new(reserved)}
+  }
+}
+
+impl Parser for IdentifierParser {
+  type I = str;
+  type O = String;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, String> {
+    match data.chars().next() {
+      Some(c) if self.style.is_start(c) => {
+        let end = data.char_indices().skip(1).find(|&(_, c)| !self.style.is_continue(c)).map_or(data.len(), |(i, _)| i);
+        let ident = &data[..end];
+        if self.reserved.contains(ident) {
+          Err(format!("`{}` is a reserved word", ident))
+        } else {
+          Ok((ident.to_string(), &data[end..]))
+        }
+      },
+      Some(_) => Err("Expected an identifier".to_string()),
+      None    => Err("ran out of data".to_string())
+    }
+  }
+}
+
+impl ParserCombinator for IdentifierParser {}
+
+
+/// A string Parser that matches a keyword only when it is not followed by an identifier character
+#[derive(Clone)]
+pub struct KeywordParser<T: Clone> {
+  pub keyword: String,
+  literal: T,
+  pub style: IdentifierStyle,
+  case: CaseMatching,
+  folded: Vec<char>,
+}
+
+impl<T: Clone> KeywordParser<T> {
+
+  /// Check the word boundary after the keyword using the given identifier style
+  pub fn with_style(&self, style: IdentifierStyle) -> Self {
+    KeywordParser{style, ..self.clone()}
+  }
+
+  /// Match the keyword regardless of the case of ASCII letters
+  pub fn ignore_ascii_case(&self) -> Self {
+    KeywordParser{case: CaseMatching::IgnoreAscii, folded: Vec::new(), ..self.clone()}
+  }
+
+  /// Match the keyword regardless of case, using Unicode case folding
+  pub fn ignore_case(&self) -> Self {
+    KeywordParser{case: CaseMatching::IgnoreUnicode, folded: fold_text(&self.keyword), ..self.clone()}
+  }
+
+  /// How the keyword is compared against the input
+  pub fn case(&self) -> CaseMatching {
+    self.case
+  }
+}
+
+impl<T: Clone> Parser for KeywordParser<T> {
+  type I = str;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, T> {
+    let rest = match match_prefix(data, &self.keyword, &self.folded, self.case) {
+      Some(len) => &data[len..],
+      None => return Err(format!("Expected keyword `{}`", self.keyword))
+    };
+    match rest.chars().next() {
+      Some(c) if self.style.is_continue(c) => Err(format!("Expected keyword `{}`", self.keyword)),
+      _ => Ok((self.literal.clone(), rest))
+    }
+  }
+}
+
+impl<T: Clone> ParserCombinator for KeywordParser<T> {}
+
+
+/// Where a `LexemeParser` skips trivia relative to its token
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipPosition {
+  Before,
+  After,
+  Both,
+}
+
+#[derive(Clone, Debug)]
+struct BlockComment {
+  open: String,
+  close: String,
+  nested: bool,
+}
+
+/// A description of the trivia (whitespace and comments) that separates tokens, created with
+/// `skipper`.  A Skipper is itself a parser that consumes any amount of trivia and returns `()`,
+/// only failing on an unterminated block comment.
+#[derive(Clone, Debug)]
+pub struct Skipper {
+  whitespace: bool,
+  line_comments: Vec<String>,
+  block_comments: Vec<BlockComment>,
+  pub position: SkipPosition,
+}
+
+impl Skipper {
+
+  /// Skip whitespace characters
+  pub fn whitespace(&self) -> Self {
+    Skipper{whitespace: true, ..self.clone()}
+  }
+
+  /// Skip comments that start with `start` and run to the end of the line.  Panics if `start` is
+  /// empty.
+  pub fn line_comment(&self, start: &str) -> Self {
+    assert!(!start.is_empty(), "Line comment start can't be empty");
+    let mut skipper = self.clone();
+    skipper.line_comments.push(start.to_string());
+    skipper
+  }
+
+  /// Skip comments delimited by `open` and `close`.  The first `close` ends the comment.  Panics if
+  /// either delimiter is empty.
+  pub fn block_comment(&self, open: &str, close: &str) -> Self {
+    check_block_delimiters(open, close);
+    let mut skipper = self.clone();
+    skipper.block_comments.push(BlockComment{open: open.to_string(), close: close.to_string(), nested: false});
+    skipper
+  }
+
+  /// Skip comments delimited by `open` and `close` that may be nested inside each other.  Panics if
+  /// either delimiter is empty.
+  pub fn nested_block_comment(&self, open: &str, close: &str) -> Self {
+    check_block_delimiters(open, close);
+    let mut skipper = self.clone();
+    skipper.block_comments.push(BlockComment{open: open.to_string(), close: close.to_string(), nested: true});
+    skipper
+  }
+
+  /// Change where lexemes skip trivia, by default it is skipped before each token
+  pub fn skip_at(&self, position: SkipPosition) -> Self {
+    Skipper{position, ..self.clone()}
+  }
+
+  /// Wrap a token parser so that it skips trivia according to this skipper's `SkipPosition`
+  pub fn lexeme<P: Parser<I=str>>(&self, p: P) -> LexemeParser<P> {
+    LexemeParser{skipper: self.clone(), parser: p}
+  }
+
+  /// Skip all trivia at the start of `data`, returning the rest
+  pub fn skip<'a>(&self, data: &'a str) -> Result<&'a str, String> {
+    self.skip_trivia(data).map_err(|offset| format!("Unterminated block comment at offset {}", offset))
+  }
+
+  /// Skip all trivia at the start of `data`, failing with the offset of an unterminated block
+  /// comment
+  fn skip_trivia<'a>(&self, data: &'a str) -> Result<&'a str, usize> {
+    let mut rest = data;
+    loop {
+      let before = rest.len();
+      if self.whitespace {
+        rest = rest.trim_start();
+      }
+      for start in self.line_comments.iter() {
+        if rest.starts_with(start.as_str()) {
+          rest = match rest.find('\n') {
+            Some(i) => &rest[i + 1..],
+            None => &rest[rest.len()..]
+          };
+        }
+      }
+      for block in self.block_comments.iter() {
+        if rest.starts_with(block.open.as_str()) {
+          rest = skip_block_comment(rest, block).ok_or(data.len() - rest.len())?;
+        }
+      }
+      if rest.len() == before {
+        return Ok(rest)
+      }
+    }
+  }
+}
+
+/// An empty delimiter would match everywhere, so either every parse would start an unterminated
+/// comment or every comment would end immediately
+fn check_block_delimiters(open: &str, close: &str) {
+  assert!(!open.is_empty() && !close.is_empty(), "Block comment delimiters can't be empty");
+}
+
+/// Skip a block comment at the start of `data`, returning None if it is never closed
+fn skip_block_comment<'a>(data: &'a str, block: &BlockComment) -> Option<&'a str> {
+  let mut rest = &data[block.open.len()..];
+  let mut depth = 1;
+  while depth > 0 {
+    if rest.starts_with(block.close.as_str()) {
+      depth -= 1;
+      rest = &rest[block.close.len()..];
+    } else if block.nested && rest.starts_with(block.open.as_str()) {
+      depth += 1;
+      rest = &rest[block.open.len()..];
+    } else {
+      let c = rest.chars().next()?;
+      rest = &rest[c.len_utf8()..];
+    }
+  }
+  Some(rest)
+}
+
+impl Parser for Skipper {
+  type I = str;
+  type O = ();
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, ()> {
+    self.skip(data).map(|rest| ((), rest))
+  }
+}
+
+impl ParserCombinator for Skipper {}
+
+
+/// A string Parser that skips trivia around another parser, see `Skipper::lexeme`
+#[derive(Clone)]
+pub struct LexemeParser<P> {
+  skipper: Skipper,
+  parser: P,
+}
+
+impl<P: Parser<I=str>> Parser for LexemeParser<P> {
+  type I = str;
+  type O = P::O;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, P::O> {
+    let position = self.skipper.position;
+    let data = if position != SkipPosition::After { self.skipper.skip(data)? } else { data };
+    let (result, rest) = self.parser.parse(data)?;
+    let rest = if position != SkipPosition::Before { self.skipper.skip(rest)? } else { rest };
+    Ok((result, rest))
+  }
+}
+
+impl<P: ParserCombinator<I=str>> ParserCombinator for LexemeParser<P> {}
+
+
+/// The location of a token in the source text.  `start` and `end` are byte offsets, `line` and
+/// `column` are the 1-based position of the first character of the token, counted in characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+  pub line: usize,
+  pub column: usize,
+}
+
+/// A token that a lexer rule matched but hasn't built yet, so that only the winning rule's token
+/// constructor is called
+type PendingToken<'a, T> = Box<dyn FnOnce() -> T + 'a>;
+
+/// A lexer rule, returning the number of bytes it matched and a way to build the token
+type LexRule<T> = Rc<dyn for<'a> Fn(&'a str) -> Option<(usize, PendingToken<'a, T>)>>;
+
+/// A lexer skip rule, returning the number of bytes it consumed, or the offset and description of
+/// an error
+type SkipRule = Rc<dyn Fn(&str) -> Result<usize, (usize, String)>>;
+
+/// A table of token and skip rules that splits a string into tokens, created with `lexer`
+pub struct Lexer<T> {
+  rules: Vec<LexRule<T>>,
+  skips: Vec<SkipRule>,
+}
+
+impl<T> Clone for Lexer<T> {
+  fn clone(&self) -> Self {
+    Lexer{rules: self.rules.clone(), skips: self.skips.clone()}
+  }
+}
+
+impl<T: 'static> Lexer<T> {
+
+  /// Add a rule that matches the regex `r` and builds a token from the matched text.
+  ///
+  /// # Panics
+  /// Panics if `r` is not a valid regex, see `try_regex` for a non-panicking version.
+  pub fn regex<F: 'static + Fn(&str) -> T>(&self, r: &str, f: F) -> Self {
+    self.try_regex(r, f).unwrap()
+  }
+
+  /// Like `regex`, but returns the regex compile error instead of panicking if `r` is not a valid
+  /// regex.  Use this when token patterns come from user input.
+  pub fn try_regex<F: 'static + Fn(&str) -> T>(&self, r: &str, f: F) -> Result<Self, Error> {
+    let regex = Regex::new(&anchored(r)?)?;
+    let f = Rc::new(f);
+    let mut lexer = self.clone();
+    lexer.rules.push(Rc::new(move |data: &str| match regex.find(data) {
+      Some((0, e)) => {
+        let f = f.clone();
+        Some((e, Box::new(move || f(&data[..e])) as PendingToken<T>))
+      },
+      _ => None
+    }));
+    Ok(lexer)
+  }
+
+  /// Add a rule that runs a string parser and builds a token from its output
+  pub fn rule<P: 'static + Parser<I=str>, F: 'static + Fn(P::O) -> T>(&self, p: P, f: F) -> Self where P::O: 'static {
+    let f = Rc::new(f);
+    let mut lexer = self.clone();
+    lexer.rules.push(Rc::new(move |data: &str| {
+      p.parse(data).ok().map(|(out, rest)| {
+        let f = f.clone();
+        (data.len() - rest.len(), Box::new(move || f(out)) as PendingToken<T>)
+      })
+    }));
+    lexer
+  }
+
+  /// Drop the trivia described by a `Skipper` between tokens.  An unterminated block comment stops
+  /// tokenizing with an error at the comment's position.
+  pub fn skip(&self, skipper: Skipper) -> Self {
+    let mut lexer = self.clone();
+    lexer.skips.push(Rc::new(move |data: &str| {
+      skipper.skip_trivia(data)
+        .map(|rest| data.len() - rest.len())
+        .map_err(|offset| (offset, "Unterminated block comment".to_string()))
+    }));
+    lexer
+  }
+
+  /// Drop any text matched by a string parser between tokens.  A failure just means there is
+  /// nothing for this rule to skip.
+  pub fn skip_rule<P: 'static + Parser<I=str>>(&self, p: P) -> Self {
+    let mut lexer = self.clone();
+    lexer.skips.push(Rc::new(move |data: &str| {
+      Ok(p.parse(data).map(|(_, rest)| data.len() - rest.len()).unwrap_or(0))
+    }));
+    lexer
+  }
+
+  /// Split all of `data` into tokens.  Fails with the line and column of the first character no
+  /// rule matches, or of the trivia a skip rule failed on.
+  pub fn tokenize(&self, data: &str) -> Result<Vec<(T, Span)>, String> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    let mut line = 1;
+    let mut column = 1;
+    loop {
+      let rest = &data[offset..];
+      let skipped = match self.skip_all(rest) {
+        Ok(skipped) => skipped,
+        Err((error_offset, err)) => {
+          advance(&rest[..error_offset], &mut line, &mut column);
+          return Err(format!("{} at line {}, column {}", err, line, column))
+        }
+      };
+      advance(&rest[..skipped], &mut line, &mut column);
+      offset += skipped;
+      let rest = &data[offset..];
+      if rest.is_empty() {
+        return Ok(tokens)
+      }
+      let mut best: Option<(usize, PendingToken<T>)> = None;
+      for rule in self.rules.iter() {
+        if let Some((len, pending)) = rule(rest) {
+          if len > 0 && best.as_ref().is_none_or(|b| len > b.0) {
+            best = Some((len, pending));
+          }
+        }
+      }
+      let (len, pending) = match best {
+        Some(b) => b,
+        None => {
+          let c = rest.chars().next().unwrap();
+          return Err(format!("Unexpected character {:?} at line {}, column {}", c, line, column))
+        }
+      };
+      tokens.push((pending(), Span{start: offset, end: offset + len, line, column}));
+      advance(&rest[..len], &mut line, &mut column);
+      offset += len;
+    }
+  }
+
+  /// Apply the skip rules until none of them consumes anything, returning the bytes skipped
+  fn skip_all(&self, data: &str) -> Result<usize, (usize, String)> {
+    let mut skipped = 0;
+    loop {
+      let before = skipped;
+      for skip in self.skips.iter() {
+        skipped += skip(&data[skipped..]).map_err(|(offset, err)| (skipped + offset, err))?;
+      }
+      if skipped == before {
+        return Ok(skipped)
+      }
+    }
+  }
+}
+
+/// Move a line and column past the given text
+fn advance(text: &str, line: &mut usize, column: &mut usize) {
+  for c in text.chars() {
+    if c == '\n' {
+      *line += 1;
+      *column = 1;
+    } else {
+      *column += 1;
+    }
+  }
+}
+
+impl<T: 'static> Parser for Lexer<T> {
+  type I = str;
+  type O = Vec<(T, Span)>;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, Self::O> {
+    self.tokenize(data).map(|tokens| (tokens, &data[data.len()..]))
+  }
+}
+
+impl<T: 'static> ParserCombinator for Lexer<T> {}
+
+
+/// The named capture groups of a regex match, handed to `FromCaptures` implementations
+pub struct NamedCaptures<'t> {
+  captures: Captures<'t>,
+  indices: Rc<HashMap<String, usize>>,
+}
+
+impl<'t> NamedCaptures<'t> {
+
+  /// The text matched by the named group, if it participated in the match
+  pub fn get(&self, name: &str) -> Option<&'t str> {
+    self.captures.name(name)
+  }
+
+  /// The byte offset of the named group's match, relative to the start of the parsed input
+  pub fn offset(&self, name: &str) -> Option<usize> {
+    self.indices.get(name).and_then(|i| self.captures.pos(*i)).map(|(s, _)| s)
+  }
+
+  /// The names of all the groups in the regex, whether or not they matched
+  pub fn names(&self) -> Vec<&str> {
+    self.indices.keys().map(|k| k.as_str()).collect()
+  }
+
+  /// Parse the text of the named group with `FromStr`.  Fails if the group didn't match or if the
+  /// conversion fails, in which case the error includes the group's offset.
+  pub fn parse<T: FromStr>(&self, name: &str) -> Result<T, String> where T::Err: Display {
+    match self.parse_opt(name) {
+      Ok(Some(t)) => Ok(t),
+      Ok(None) => Err(format!("Missing capture group `{}`", name)),
+      Err(err) => Err(err)
+    }
+  }
+
+  /// Like `parse`, but returns `None` if the named group didn't match
+  pub fn parse_opt<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> where T::Err: Display {
+    match self.get(name) {
+      Some(text) => T::from_str(text).map(Some).map_err(|err| {
+        format!("Invalid value for capture group `{}` at offset {}: {}", name, self.offset(name).unwrap_or(0), err)
+      }),
+      None => Ok(None)
+    }
+  }
+}
+
+/// Types that can be built from the named capture groups of a regex match, see `named_capture`
+pub trait FromCaptures: Sized {
+  fn from_captures(captures: &NamedCaptures) -> Result<Self, String>;
+}
 
-  RegexCapturesParser{regex: regex, f: Rc::new(Box::new(f))}
+impl FromCaptures for HashMap<String, String> {
+  fn from_captures(captures: &NamedCaptures) -> Result<Self, String> {
+    Ok(captures.names().into_iter().filter_map(|name| captures.get(name).map(|text| (name.to_string(), text.to_string()))).collect())
+  }
+}
+
+/// A string Parser that matches a regex and builds its output from the named capture groups
+pub struct NamedCaptureParser<T: FromCaptures> {
+  pub regex: Regex,
+  indices: Rc<HashMap<String, usize>>,
+  _marker: PhantomData<T>
 }
 
+impl<T: FromCaptures> Parser for NamedCaptureParser<T> {
+  type I = str;
+  type O = T;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, T> {
+    match self.regex.captures(data) {
+      Some(caps) => match caps.pos(0) {
+        Some((0, e)) => {
+          let captures = NamedCaptures{captures: caps, indices: self.indices.clone()};
+          T::from_captures(&captures).map(|t| (t, &data[e..]))
+        },
+        _ => Err("No Match".to_string())
+      },
+      None => Err("No Match".to_string())
+    }
+  }
+}
+
+impl<T: FromCaptures> ParserCombinator for NamedCaptureParser<T> {}
+
+impl<T: FromCaptures> Clone for NamedCaptureParser<T> {
+  fn clone(&self) -> Self {
+    NamedCaptureParser{regex: self.regex.clone(), indices: self.indices.clone(), _marker: PhantomData}
+  }
+}
+
+
+/// A string Parser that consumes text up to the first position where its `end` parser matches
+#[derive(Clone)]
+pub struct StrTakeUntilParser<P> {
+  end: P,
+}
+
+impl<P: Parser<I=str>> Parser for StrTakeUntilParser<P> {
+  type I = str;
+  type O = String;
+
+  fn parse<'a>(&self, data: &'a str) -> ParseResult<&'a str, String> {
+    let positions = data.char_indices().map(|(i, _)| i).chain(Some(data.len()));
+    for i in positions {
+      if self.end.parse(&data[i..]).is_ok() {
+        return Ok((data[..i].to_string(), &data[i..]))
+      }
+    }
+    Err("ran out of data before terminator".to_string())
+  }
+}
+
+impl<P: ParserCombinator<I=str>> ParserCombinator for StrTakeUntilParser<P> {}
+